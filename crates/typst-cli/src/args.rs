@@ -64,7 +64,7 @@ pub struct CompileCommand {
     #[clap(flatten)]
     pub common: SharedArgs,
 
-    /// Path to output file (PDF, PNG, or SVG)
+    /// Path to output file (PDF, PNG, SVG, or PS)
     #[clap(required_if_eq("input", "-"))]
     pub output: Option<PathBuf>,
 
@@ -87,6 +87,14 @@ pub struct CompileCommand {
     /// apart from file names and line numbers.
     #[arg(long = "timings", value_name = "OUTPUT_JSON")]
     pub timings: Option<Option<PathBuf>>,
+
+    /// Writes a map from the output's text runs to their source ranges
+    ///
+    /// The resulting JSON file lists each run of text with its page, position
+    /// and the byte ranges in the source files it was produced from, e.g. for
+    /// redaction tools.
+    #[arg(long = "text-map", value_name = "OUTPUT_JSON")]
+    pub text_map: Option<PathBuf>,
 }
 
 /// Processes an input file to extract provided metadata
@@ -248,6 +256,7 @@ pub enum OutputFormat {
     Pdf,
     Png,
     Svg,
+    Ps,
 }
 
 impl Display for OutputFormat {
@@ -7,7 +7,7 @@ use codespan_reporting::term;
 use ecow::{eco_format, EcoString};
 use parking_lot::RwLock;
 use rayon::iter::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
-use typst::diag::{bail, At, Severity, SourceDiagnostic, StrResult};
+use typst::diag::{bail, At, Severity, SourceDiagnostic, SourceResult, StrResult};
 use typst::eval::Tracer;
 use typst::foundations::Datetime;
 use typst::layout::Frame;
@@ -17,6 +17,7 @@ use typst::visualize::Color;
 use typst::{World, WorldExt};
 
 use crate::args::{CompileCommand, DiagnosticFormat, Input, OutputFormat};
+use crate::text_map::write_text_map;
 use crate::timings::Timer;
 use crate::watch::Status;
 use crate::world::SystemWorld;
@@ -37,6 +38,7 @@ impl CompileCommand {
                     OutputFormat::Pdf => "pdf",
                     OutputFormat::Png => "png",
                     OutputFormat::Svg => "svg",
+                    OutputFormat::Ps => "ps",
                 },
             )
         })
@@ -53,6 +55,7 @@ impl CompileCommand {
                 Some(ext) if ext.eq_ignore_ascii_case("pdf") => OutputFormat::Pdf,
                 Some(ext) if ext.eq_ignore_ascii_case("png") => OutputFormat::Png,
                 Some(ext) if ext.eq_ignore_ascii_case("svg") => OutputFormat::Svg,
+                Some(ext) if ext.eq_ignore_ascii_case("ps") => OutputFormat::Ps,
                 _ => bail!("could not infer output format for path {}.\nconsider providing the format manually with `--format/-f`", output.display()),
             }
         } else {
@@ -97,7 +100,8 @@ pub fn compile_once(
     }
 
     let mut tracer = Tracer::new();
-    let result = typst::compile(world, &mut tracer);
+    let result = typst::compile(world, &mut tracer)
+        .and_then(|document| check_export(&document, command).map(|()| document));
     let warnings = tracer.warnings();
 
     match result {
@@ -150,6 +154,10 @@ fn export(
     command: &CompileCommand,
     watching: bool,
 ) -> StrResult<()> {
+    if let Some(path) = &command.text_map {
+        write_text_map(world, document, path)?;
+    }
+
     match command.output_format()? {
         OutputFormat::Png => {
             export_image(world, document, command, watching, ImageExportFormat::Png)
@@ -158,6 +166,18 @@ fn export(
             export_image(world, document, command, watching, ImageExportFormat::Svg)
         }
         OutputFormat::Pdf => export_pdf(document, command, world),
+        OutputFormat::Ps => export_ps(document, command),
+    }
+}
+
+/// Ensure that the document can be exported into the target format.
+///
+/// PostScript cannot express all content. This is reported like any other
+/// error in the document, so that it points to the offending content.
+fn check_export(document: &Document, command: &CompileCommand) -> SourceResult<()> {
+    match command.output_format() {
+        Ok(OutputFormat::Ps) => typst_ps::check(document),
+        _ => Ok(()),
     }
 }
 
@@ -175,6 +195,15 @@ fn export_pdf(
     Ok(())
 }
 
+/// Export to a PostScript file.
+fn export_ps(document: &Document, command: &CompileCommand) -> StrResult<()> {
+    let buffer = typst_ps::ps(document).map_err(|errors| errors[0].message.clone())?;
+    let output = command.output();
+    fs::write(output, buffer)
+        .map_err(|err| eco_format!("failed to write PostScript file ({err})"))?;
+    Ok(())
+}
+
 /// Get the current date and time in UTC.
 fn now() -> Option<Datetime> {
     let now = chrono::Local::now().naive_utc();
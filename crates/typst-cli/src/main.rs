@@ -5,6 +5,7 @@ mod fonts;
 mod package;
 mod query;
 mod terminal;
+mod text_map;
 mod timings;
 #[cfg(feature = "self-update")]
 mod update;
@@ -0,0 +1,211 @@
+use std::fs;
+use std::num::NonZeroUsize;
+use std::ops::Range;
+use std::path::Path;
+
+use ecow::{eco_format, EcoString};
+use serde::Serialize;
+use typst::diag::StrResult;
+use typst::layout::{Frame, FrameItem, Transform};
+use typst::model::Document;
+use typst::syntax::{FileId, Span};
+use typst::text::TextItem;
+use typst::WorldExt;
+
+use crate::world::SystemWorld;
+
+/// A run of text in the exported document together with the source code it
+/// was produced from.
+///
+/// Redaction tools use this to find the text that corresponds to a logical
+/// span of the source and remove it from the output.
+#[derive(Debug, Serialize)]
+struct TextMapEntry {
+    /// The page the run is on, starting at 1.
+    page: NonZeroUsize,
+    /// The horizontal position of the run's baseline start in points.
+    x: f64,
+    /// The vertical position of the run's baseline start in points.
+    y: f64,
+    /// The width of the run in points.
+    width: f64,
+    /// The font size of the run in points.
+    size: f64,
+    /// The run's text.
+    text: EcoString,
+    /// The source ranges that the run's glyphs were produced from. Adjacent
+    /// ranges are merged and glyphs that don't stem from a source file are
+    /// left out.
+    sources: Vec<SourceRange>,
+}
+
+/// A byte range in a source file.
+#[derive(Debug, Serialize)]
+struct SourceRange {
+    /// The file's path, prefixed with its package if any.
+    file: String,
+    /// The start of the range in bytes.
+    start: usize,
+    /// The end of the range in bytes.
+    end: usize,
+}
+
+/// Write a JSON map from the document's text runs to their source code.
+pub fn write_text_map(
+    world: &SystemWorld,
+    document: &Document,
+    path: &Path,
+) -> StrResult<()> {
+    let json = text_map(document, |span| world.range(span))?;
+    fs::write(path, json).map_err(|err| eco_format!("failed to write text map ({err})"))
+}
+
+/// Serialize the text map of a document into JSON, resolving spans to byte
+/// ranges through the given function.
+fn text_map(
+    document: &Document,
+    resolve: impl Fn(Span) -> Option<Range<usize>>,
+) -> StrResult<String> {
+    let mut entries = vec![];
+    for (i, page) in document.pages.iter().enumerate() {
+        let nr = NonZeroUsize::new(1 + i).unwrap();
+        collect(&mut entries, &page.frame, nr, Transform::identity(), &resolve);
+    }
+    serde_json::to_string_pretty(&entries).map_err(|err| eco_format!("{err}"))
+}
+
+/// Collect the text runs in a frame.
+fn collect(
+    entries: &mut Vec<TextMapEntry>,
+    frame: &Frame,
+    page: NonZeroUsize,
+    ts: Transform,
+    resolve: &dyn Fn(Span) -> Option<Range<usize>>,
+) {
+    for (pos, item) in frame.items() {
+        match item {
+            FrameItem::Group(group) => {
+                let ts = ts
+                    .pre_concat(Transform::translate(pos.x, pos.y))
+                    .pre_concat(group.transform);
+                collect(entries, &group.frame, page, ts, resolve);
+            }
+            FrameItem::Text(text) => {
+                let point = pos.transform(ts);
+                entries.push(TextMapEntry {
+                    page,
+                    x: point.x.to_pt(),
+                    y: point.y.to_pt(),
+                    width: text.width().to_pt(),
+                    size: text.size.to_pt(),
+                    text: text.text.clone(),
+                    sources: sources(text, resolve),
+                });
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Determine the source ranges of a text run's glyphs.
+fn sources(
+    text: &TextItem,
+    resolve: &dyn Fn(Span) -> Option<Range<usize>>,
+) -> Vec<SourceRange> {
+    let mut sources: Vec<(FileId, Range<usize>)> = vec![];
+    for glyph in &text.glyphs {
+        let (span, offset) = glyph.span;
+        let Some(id) = span.id() else { continue };
+        let Some(range) = resolve(span) else { continue };
+        let start = (range.start + usize::from(offset)).min(range.end);
+        let end = (start + glyph.range().len()).min(range.end);
+
+        match sources.last_mut() {
+            Some((last_id, last)) if *last_id == id && last.end == start => {
+                last.end = end;
+            }
+            _ => sources.push((id, start..end)),
+        }
+    }
+
+    sources
+        .into_iter()
+        .map(|(id, range)| {
+            let path = id.vpath().as_rooted_path().display();
+            SourceRange {
+                file: match id.package() {
+                    Some(package) => format!("{package}{path}"),
+                    None => path.to_string(),
+                },
+                start: range.start,
+                end: range.end,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use typst::foundations::Bytes;
+    use typst::layout::{Abs, Em, Frame, FrameItem, Page, Point, Size};
+    use typst::model::Document;
+    use typst::syntax::{Source, Span};
+    use typst::text::{Font, Glyph, Lang, TextItem};
+    use typst::visualize::{Color, Paint};
+
+    use super::text_map;
+
+    #[test]
+    fn test_text_map_points_to_source() {
+        let source = Source::detached("= Hello");
+        let span = source.root().span();
+        let font = typst_assets::fonts()
+            .find_map(|data| Font::new(Bytes::from_static(data), 0))
+            .unwrap();
+
+        // The last glyph is not from the source file, e.g. a generated suffix.
+        let glyphs = "Hello!"
+            .char_indices()
+            .map(|(i, _)| Glyph {
+                id: 0,
+                x_advance: Em::new(0.5),
+                x_offset: Em::zero(),
+                range: i as u16..i as u16 + 1,
+                span: match i {
+                    0..=4 => (span, 2 + i as u16),
+                    _ => (Span::detached(), 0),
+                },
+            })
+            .collect();
+        let text = TextItem {
+            font,
+            size: Abs::pt(10.0),
+            fill: Paint::Solid(Color::BLACK),
+            stroke: None,
+            lang: Lang::ENGLISH,
+            text: "Hello!".into(),
+            glyphs,
+        };
+
+        let mut frame = Frame::soft(Size::splat(Abs::pt(100.0)));
+        frame.push(Point::new(Abs::pt(10.0), Abs::pt(20.0)), FrameItem::Text(text));
+        let page = Page { frame, numbering: None, number: 1 };
+        let document = Document { pages: vec![page], ..Default::default() };
+
+        let json = text_map(&document, |span| source.range(span)).unwrap();
+        let map: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            map,
+            serde_json::json!([{
+                "page": 1,
+                "x": 10.0,
+                "y": 20.0,
+                "width": 30.0,
+                "size": 10.0,
+                "text": "Hello!",
+                "sources": [{ "file": "/main.typ", "start": 2, "end": 7 }],
+            }])
+        );
+        assert_eq!(&source.text()[2..7], "Hello");
+    }
+}
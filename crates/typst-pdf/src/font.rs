@@ -151,10 +151,13 @@ pub(crate) fn write_fonts(ctx: &mut PdfContext) {
 
         // Subset and write the font's bytes.
         let glyphs: Vec<_> = glyph_set.keys().copied().collect();
-        let data = subset_font(font, &glyphs);
+        let compress = !ctx.options.uncompressed;
+        let data = subset_font(font, &glyphs, compress);
 
         let mut stream = ctx.pdf.stream(data_ref, &data);
-        stream.filter(Filter::FlateDecode);
+        if compress {
+            stream.filter(Filter::FlateDecode);
+        }
         if is_cff {
             stream.pair(Name(b"Subtype"), Name(b"CIDFontType0C"));
         }
@@ -163,13 +166,25 @@ pub(crate) fn write_fonts(ctx: &mut PdfContext) {
     }
 }
 
+/// Subset a font to the given glyphs, the same way as for embedding it into a
+/// PDF file.
+///
+/// This lets other exporters that embed fonts share the subsetting. The
+/// result is never compressed.
+///
+/// - For a font with TrueType outlines, this returns the whole OpenType font.
+/// - For a font with CFF outlines, this returns just the CFF font program.
+pub fn subset_font_data(font: &Font, glyphs: &[u16]) -> Vec<u8> {
+    subset_font(font, glyphs, false).to_vec()
+}
+
 /// Subset a font to the given glyphs.
 ///
 /// - For a font with TrueType outlines, this returns the whole OpenType font.
 /// - For a font with CFF outlines, this returns just the CFF font program.
 #[comemo::memoize]
 #[typst_macros::time(name = "subset font")]
-fn subset_font(font: &Font, glyphs: &[u16]) -> Arc<Vec<u8>> {
+fn subset_font(font: &Font, glyphs: &[u16], compress: bool) -> Arc<Vec<u8>> {
     let data = font.data();
     let profile = subsetter::Profile::pdf(glyphs);
     let subsetted = subsetter::subset(data, font.index(), profile);
@@ -181,7 +196,7 @@ fn subset_font(font: &Font, glyphs: &[u16]) -> Arc<Vec<u8>> {
         data = cff;
     }
 
-    Arc::new(deflate(data))
+    Arc::new(if compress { deflate(data) } else { data.to_vec() })
 }
 
 /// Produce a unique 6 letter tag for a glyph set.
@@ -266,3 +281,41 @@ pub(super) fn glyph_cid(font: &Font, glyph_id: u16) -> u16 {
         .and_then(|cff| cff.glyph_cid(ttf_parser::GlyphId(glyph_id)))
         .unwrap_or(glyph_id)
 }
+
+#[cfg(test)]
+mod tests {
+    use ttf_parser::GlyphId;
+    use typst::foundations::Bytes;
+    use typst::text::Font;
+
+    use super::subset_font_data;
+
+    /// The first TrueType font among the bundled fonts.
+    fn truetype_font() -> Font {
+        typst_assets::fonts()
+            .flat_map(|data| Font::iter(Bytes::from_static(data)))
+            .find(|font| font.ttf().tables().glyf.is_some())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_subset_preserves_glyph_ids() {
+        let font = truetype_font();
+        let gid = |c| font.ttf().glyph_index(c).unwrap();
+        let glyphs: Vec<u16> = "Typst".chars().map(|c| gid(c).0).collect();
+        let data = subset_font_data(&font, &glyphs);
+        let subset = ttf_parser::Face::parse(&data, 0).unwrap();
+
+        // TrueType fonts are written with an identity `/CIDToGIDMap`, so each
+        // used glyph must keep its ID in the subset.
+        for &g in &glyphs {
+            let bbox = font.ttf().glyph_bounding_box(GlyphId(g));
+            assert!(bbox.is_some());
+            assert_eq!(subset.glyph_bounding_box(GlyphId(g)), bbox);
+        }
+
+        // Unused glyphs are dropped.
+        assert!(font.ttf().glyph_bounding_box(gid('Q')).is_some());
+        assert_eq!(subset.glyph_bounding_box(gid('Q')), None);
+    }
+}
@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 use std::io::Cursor;
 
-use image::{DynamicImage, GenericImageView, Rgba};
+use image::{DynamicImage, GenericImageView, LumaA, Rgba};
 use pdf_writer::{Chunk, Filter, Finish, Ref};
 use typst::util::Deferred;
 use typst::visualize::{
@@ -12,18 +12,24 @@ use crate::{deflate, PdfContext};
 
 /// Creates a new PDF image from the given image.
 ///
-/// Also starts the deferred encoding of the image.
+/// Also starts the deferred encoding of the image. If `flatten` is set, an
+/// alpha channel is composited over white instead of being encoded as a soft
+/// mask.
 #[comemo::memoize]
-pub fn deferred_image(image: Image) -> Deferred<EncodedImage> {
+pub fn deferred_image(image: Image, flatten: bool) -> Deferred<EncodedImage> {
     Deferred::new(move || match image.kind() {
         ImageKind::Raster(raster) => {
             let raster = raster.clone();
             let (width, height) = (raster.width(), raster.height());
-            let (data, filter, has_color) = encode_raster_image(&raster);
+            let has_alpha = raster.dynamic().color().has_alpha();
+            let (data, filter, has_color) = if flatten && has_alpha {
+                encode_flattened_image(&raster)
+            } else {
+                encode_raster_image(&raster)
+            };
             let icc = raster.icc().map(deflate);
 
-            let alpha =
-                raster.dynamic().color().has_alpha().then(|| encode_alpha(&raster));
+            let alpha = (has_alpha && !flatten).then(|| encode_alpha(&raster));
 
             EncodedImage::Raster { data, filter, has_color, width, height, icc, alpha }
         }
@@ -134,6 +140,35 @@ fn encode_raster_image(image: &RasterImage) -> (Vec<u8>, Filter, bool) {
     }
 }
 
+/// Encode an image with an alpha channel composited over white and return the
+/// data, filter and whether the image has color.
+fn encode_flattened_image(image: &RasterImage) -> (Vec<u8>, Filter, bool) {
+    let dynamic = image.dynamic();
+    let has_color = dynamic.color().channel_count() > 2;
+    let over_white = |c: u8, a: u8| {
+        let (c, a) = (u16::from(c), u16::from(a));
+        ((c * a + 255 * (255 - a) + 127) / 255) as u8
+    };
+
+    let data: Vec<u8> = if has_color {
+        dynamic
+            .to_rgba8()
+            .pixels()
+            .flat_map(|&Rgba([r, g, b, a])| {
+                [over_white(r, a), over_white(g, a), over_white(b, a)]
+            })
+            .collect()
+    } else {
+        dynamic
+            .to_luma_alpha8()
+            .pixels()
+            .map(|&LumaA([l, a])| over_white(l, a))
+            .collect()
+    };
+
+    (deflate(&data), Filter::FlateDecode, has_color)
+}
+
 /// Encode an image's alpha channel if present.
 fn encode_alpha(raster: &RasterImage) -> (Vec<u8>, Filter) {
     let pixels: Vec<_> = raster
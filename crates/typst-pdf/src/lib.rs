@@ -8,24 +8,28 @@ mod image;
 mod outline;
 mod page;
 mod pattern;
+mod structure;
+
+pub use self::font::subset_font_data;
 
 use std::cmp::Eq;
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::hash::Hash;
+use std::num::NonZeroUsize;
 use std::sync::Arc;
 
 use base64::Engine;
 use ecow::{eco_format, EcoString};
 use pdf_writer::types::Direction;
 use pdf_writer::writers::Destination;
-use pdf_writer::{Finish, Name, Pdf, Ref, Str, TextStr};
+use pdf_writer::{Dict, Finish, Name, Pdf, Ref, Str, TextStr};
 use typst::foundations::{Datetime, Label, NativeElement};
 use typst::introspection::Location;
-use typst::layout::{Abs, Dir, Em, Transform};
+use typst::layout::{Abs, Dir, Em, Position, Size, Transform};
 use typst::model::{Document, HeadingElem};
 use typst::text::{Font, Lang};
 use typst::util::Deferred;
-use typst::visualize::Image;
+use typst::visualize::{Color, Image};
 use xmp_writer::{DateTime, LangId, RenditionClass, Timezone, XmpWriter};
 
 use crate::color::ColorSpaces;
@@ -49,13 +53,23 @@ use crate::pattern::PdfPattern;
 /// The `timestamp`, if given, is expected to be the creation date of the
 /// document as a UTC datetime. It will only be used if `set document(date: ..)`
 /// is `auto`.
-#[typst_macros::time(name = "pdf")]
+///
+/// To configure the export further, use [`pdf_with_options`].
 pub fn pdf(
     document: &Document,
     ident: Option<&str>,
     timestamp: Option<Datetime>,
 ) -> Vec<u8> {
-    let mut ctx = PdfContext::new(document);
+    let options = PdfOptions::default().with_ident(ident).with_timestamp(timestamp);
+    pdf_with_options(document, &options)
+}
+
+/// Export a document into a PDF file with the given settings.
+///
+/// Returns the raw bytes making up the PDF file.
+#[typst_macros::time(name = "pdf")]
+pub fn pdf_with_options(document: &Document, options: &PdfOptions) -> Vec<u8> {
+    let mut ctx = PdfContext::new(document, options);
     page::construct_pages(&mut ctx, &document.pages);
     font::write_fonts(&mut ctx);
     image::write_images(&mut ctx);
@@ -64,14 +78,227 @@ pub fn pdf(
     pattern::write_patterns(&mut ctx);
     write_named_destinations(&mut ctx);
     page::write_page_tree(&mut ctx);
-    write_catalog(&mut ctx, ident, timestamp);
+    write_catalog(&mut ctx);
     ctx.pdf.finish()
 }
 
+/// Settings for PDF export.
+///
+/// Start from the default settings and adjust them with the `with_*` methods.
+#[derive(Debug, Default, Clone)]
+#[non_exhaustive]
+pub struct PdfOptions<'a> {
+    /// A string that uniquely and stably identifies the document.
+    ///
+    /// It should not change between compilations of the same document. Its
+    /// hash will be used to create a PDF document identifier (the identifier
+    /// itself is not leaked). If `None`, a hash of the document is used
+    /// instead (which means that it _will_ change across compilations).
+    pub ident: Option<&'a str>,
+    /// The creation date of the document as a UTC datetime.
+    ///
+    /// It will only be used if `set document(date: ..)` is `auto`.
+    pub timestamp: Option<Datetime>,
+    /// How the glyphs of text runs are encoded into PDF strings.
+    pub text_encoding: TextEncoding,
+    /// Whether to draw text as vector outlines instead of with font glyphs.
+    ///
+    /// The text is still written as an invisible layer on top of the
+    /// outlines, so that it remains searchable and can be copied.
+    pub outline_text: bool,
+    /// Whether to write a logical structure tree. Headings are tagged as `H1`
+    /// to `H6` depending on their level and their content is wrapped in
+    /// marked-content sequences.
+    ///
+    /// The rest of the content is not tagged yet, so the document is neither
+    /// marked as a tagged PDF nor identified as PDF/UA.
+    pub tagged: bool,
+    /// Provenance metadata for individual pages.
+    pub page_metadata: Vec<PdfPageMetadata>,
+    /// Sticky notes to attach to the document's pages.
+    ///
+    /// Notes are pure overlays and don't affect the layout of the document.
+    pub notes: Vec<PdfNote>,
+    /// Application-private data to attach to the document or its pages.
+    pub piece_info: Vec<PdfPieceInfo>,
+    /// Interactive 3D models to embed into the document's pages.
+    pub models: Vec<PdfModel>,
+    /// Whether to write a flattened PDF without any transparency.
+    ///
+    /// Translucent colors, including those of gradient stops and within
+    /// patterns, and raster images with an alpha channel are composited over
+    /// white, so pages need no transparency group, graphics states with alpha
+    /// or soft masks. This matches the appearance of translucent content placed
+    /// directly on an unfilled page. SVG images are embedded as-is.
+    pub flatten: bool,
+    /// Whether to write page content streams and font programs without
+    /// compression. This makes the PDF larger, but keeps its drawing
+    /// operators readable when debugging the output.
+    pub uncompressed: bool,
+}
+
+impl<'a> PdfOptions<'a> {
+    /// Set the string that identifies the document.
+    pub fn with_ident(mut self, ident: Option<&'a str>) -> Self {
+        self.ident = ident;
+        self
+    }
+
+    /// Set the creation date of the document.
+    pub fn with_timestamp(mut self, timestamp: Option<Datetime>) -> Self {
+        self.timestamp = timestamp;
+        self
+    }
+
+    /// Set how the glyphs of text runs are encoded.
+    pub fn with_text_encoding(mut self, text_encoding: TextEncoding) -> Self {
+        self.text_encoding = text_encoding;
+        self
+    }
+
+    /// Set whether to draw text as vector outlines.
+    pub fn with_outline_text(mut self, outline_text: bool) -> Self {
+        self.outline_text = outline_text;
+        self
+    }
+
+    /// Set whether to write a logical structure tree.
+    pub fn with_tagged(mut self, tagged: bool) -> Self {
+        self.tagged = tagged;
+        self
+    }
+
+    /// Set the provenance metadata for individual pages.
+    pub fn with_page_metadata(mut self, page_metadata: Vec<PdfPageMetadata>) -> Self {
+        self.page_metadata = page_metadata;
+        self
+    }
+
+    /// Set the sticky notes to attach to the document's pages.
+    pub fn with_notes(mut self, notes: Vec<PdfNote>) -> Self {
+        self.notes = notes;
+        self
+    }
+
+    /// Set the application-private data to attach to the document or its
+    /// pages.
+    pub fn with_piece_info(mut self, piece_info: Vec<PdfPieceInfo>) -> Self {
+        self.piece_info = piece_info;
+        self
+    }
+
+    /// Set the interactive 3D models to embed into the document's pages.
+    pub fn with_models(mut self, models: Vec<PdfModel>) -> Self {
+        self.models = models;
+        self
+    }
+
+    /// Set whether to write a flattened PDF without any transparency.
+    pub fn with_flatten(mut self, flatten: bool) -> Self {
+        self.flatten = flatten;
+        self
+    }
+
+    /// Set whether to write content streams and font programs without
+    /// compression.
+    pub fn with_uncompressed(mut self, uncompressed: bool) -> Self {
+        self.uncompressed = uncompressed;
+        self
+    }
+}
+
+/// Provenance metadata for a single page, written as the page's own XMP
+/// metadata stream.
+#[derive(Debug, Clone, PartialEq, Hash)]
+pub struct PdfPageMetadata {
+    /// The page, starting at 1. Metadata for pages that don't exist is
+    /// ignored.
+    pub page: NonZeroUsize,
+    /// Identifies the source the page was produced from, e.g. a file path.
+    pub source: EcoString,
+    /// When the page was produced, as a UTC datetime.
+    pub date: Option<Datetime>,
+}
+
+/// Application-private data, written into a `/PieceInfo` dictionary.
+///
+/// Readers ignore this data, but the application that wrote it can read it
+/// back by its name, e.g. to reconstruct its own structures when editing a
+/// previously exported document.
+///
+/// As the PDF standard requires a modification date alongside the data, it
+/// is only written if the document's date or the export's timestamp is known.
+#[derive(Debug, Clone, PartialEq, Hash)]
+pub struct PdfPieceInfo {
+    /// The page to attach the data to, starting at 1, or `None` to attach it
+    /// to the whole document. Data for pages that don't exist is ignored.
+    pub page: Option<NonZeroUsize>,
+    /// The name of the application that owns the data. If there are multiple
+    /// entries for the same application and target, the last one wins.
+    pub app: EcoString,
+    /// The data itself.
+    pub data: Vec<u8>,
+}
+
+/// A sticky note that is exported as a text annotation.
+#[derive(Debug, Clone, PartialEq, Hash)]
+pub struct PdfNote {
+    /// Where to place the note's icon. Notes on pages that don't exist are
+    /// ignored.
+    pub pos: Position,
+    /// The note's text.
+    pub contents: EcoString,
+    /// Who wrote the note.
+    pub author: Option<EcoString>,
+    /// The color of the note's icon and popup.
+    pub color: Option<Color>,
+}
+
+/// An interactive 3D model that is exported as a 3D annotation.
+#[derive(Debug, Clone, PartialEq, Hash)]
+pub struct PdfModel {
+    /// The top-left corner of the area the model is shown in. Models on pages
+    /// that don't exist are ignored.
+    pub pos: Position,
+    /// The size of the area the model is shown in.
+    pub size: Size,
+    /// The format of the model data.
+    pub format: ModelFormat,
+    /// The model data, which is embedded as-is.
+    pub data: Vec<u8>,
+}
+
+/// The format of a 3D model's data.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum ModelFormat {
+    /// Universal 3D, as specified by ECMA-363.
+    U3d,
+    /// Product Representation Compact, as specified by ISO 14739-1.
+    Prc,
+}
+
+/// How text is encoded into the strings of a content stream's text showing
+/// operators.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum TextEncoding {
+    /// Use whichever of the literal and hex encodings is shorter for each
+    /// string.
+    #[default]
+    Auto,
+    /// Write literal strings, e.g. `(Hi\(\))`. Parentheses and backslashes
+    /// are escaped with a backslash, bytes outside of printable ASCII are
+    /// written as octal escapes.
+    Literal,
+    /// Write hex strings, e.g. `<48692829>`.
+    Hex,
+}
+
 /// Context for exporting a whole PDF document.
 struct PdfContext<'a> {
     /// The document that we're currently exporting.
     document: &'a Document,
+    /// The settings for the export.
+    options: &'a PdfOptions<'a>,
     /// The writer we are writing the PDF into.
     pdf: Pdf,
     /// Content of exported pages.
@@ -127,11 +354,12 @@ struct PdfContext<'a> {
 }
 
 impl<'a> PdfContext<'a> {
-    fn new(document: &'a Document) -> Self {
+    fn new(document: &'a Document, options: &'a PdfOptions<'a>) -> Self {
         let mut alloc = Ref::new(1);
         let page_tree_ref = alloc.bump();
         Self {
             document,
+            options,
             pdf: Pdf::new(),
             pages: vec![],
             glyph_sets: HashMap::new(),
@@ -158,7 +386,7 @@ impl<'a> PdfContext<'a> {
 }
 
 /// Write the document catalog.
-fn write_catalog(ctx: &mut PdfContext, ident: Option<&str>, timestamp: Option<Datetime>) {
+fn write_catalog(ctx: &mut PdfContext) {
     let lang = ctx.languages.iter().max_by_key(|(_, &count)| count).map(|(&l, _)| l);
 
     let dir = if lang.map(Lang::dir) == Some(Dir::RTL) {
@@ -173,6 +401,10 @@ fn write_catalog(ctx: &mut PdfContext, ident: Option<&str>, timestamp: Option<Da
     // Write the page labels.
     let page_labels = page::write_page_labels(ctx);
 
+    // Write the structure tree.
+    let struct_tree_root =
+        ctx.options.tagged.then(|| structure::write_structure_tree(ctx));
+
     // Write the document information.
     let mut info = ctx.pdf.document_info(ctx.alloc.bump());
     let mut xmp = XmpWriter::new();
@@ -215,7 +447,7 @@ fn write_catalog(ctx: &mut PdfContext, ident: Option<&str>, timestamp: Option<Da
         xmp.pdf_keywords(&joined);
     }
 
-    if let Some(date) = ctx.document.date.unwrap_or(timestamp) {
+    if let Some(date) = ctx.document.date.unwrap_or(ctx.options.timestamp) {
         let tz = ctx.document.date.is_auto();
         if let Some(pdf_date) = pdf_date(date, tz) {
             info.creation_date(pdf_date);
@@ -236,7 +468,7 @@ fn write_catalog(ctx: &mut PdfContext, ident: Option<&str>, timestamp: Option<Da
     // changes in the frames.
     let instance_id = hash_base64(&ctx.pdf.as_bytes());
 
-    if let Some(ident) = ident {
+    if let Some(ident) = ctx.options.ident {
         // A unique ID for the document that stays stable across compilations.
         let doc_id = hash_base64(&("PDF-1.7", ident));
         xmp.document_id(&doc_id);
@@ -260,6 +492,7 @@ fn write_catalog(ctx: &mut PdfContext, ident: Option<&str>, timestamp: Option<Da
         .pair(Name(b"Subtype"), Name(b"XML"));
 
     // Write the document catalog.
+    let modified = modified_date(ctx);
     let mut catalog = ctx.pdf.catalog(ctx.alloc.bump());
     catalog.pages(ctx.page_tree_ref);
     catalog.viewer_preferences().direction(dir);
@@ -293,9 +526,49 @@ fn write_catalog(ctx: &mut PdfContext, ident: Option<&str>, timestamp: Option<Da
         catalog.lang(TextStr(lang.as_str()));
     }
 
+    if let Some(struct_tree_root) = struct_tree_root {
+        catalog.pair(Name(b"StructTreeRoot"), struct_tree_root);
+    }
+
+    // Application data must state when it was last modified, so it is only
+    // written if that is known.
+    if let Some(modified) = modified {
+        let pieces = ctx.options.piece_info.iter().filter(|piece| piece.page.is_none());
+        write_piece_info(&mut catalog, pieces, modified);
+    }
+
     catalog.finish();
 }
 
+/// Write a `/PieceInfo` dictionary holding the given applications' data, if
+/// there is any.
+fn write_piece_info<'a>(
+    dict: &mut Dict,
+    pieces: impl IntoIterator<Item = &'a PdfPieceInfo>,
+    modified: pdf_writer::Date,
+) {
+    let pieces: BTreeMap<&str, &[u8]> = pieces
+        .into_iter()
+        .map(|piece| (piece.app.as_str(), piece.data.as_slice()))
+        .collect();
+    if pieces.is_empty() {
+        return;
+    }
+
+    let mut piece_info = dict.insert(Name(b"PieceInfo")).dict();
+    for (app, data) in pieces {
+        let mut entry = piece_info.insert(Name(app.as_bytes())).dict();
+        entry.pair(Name(b"LastModified"), modified);
+        entry.pair(Name(b"Private"), Str(data));
+    }
+}
+
+/// The date at which the document was last modified, if known.
+fn modified_date(ctx: &PdfContext) -> Option<pdf_writer::Date> {
+    let date = ctx.document.date.unwrap_or(ctx.options.timestamp)?;
+    pdf_date(date, ctx.document.date.is_auto())
+}
+
 /// Fills in the map and vector for named destinations and writes the indirect
 /// destination objects.
 fn write_named_destinations(ctx: &mut PdfContext) {
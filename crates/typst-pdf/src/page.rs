@@ -1,28 +1,37 @@
 use std::collections::HashMap;
 use std::num::NonZeroUsize;
+use std::sync::Arc;
 
 use ecow::{eco_format, EcoString};
 use pdf_writer::types::{
     ActionType, AnnotationFlags, AnnotationType, ColorSpaceOperand, LineCapStyle,
-    LineJoinStyle, NumberingStyle,
+    LineJoinStyle, NumberingStyle, TextRenderingMode,
 };
 use pdf_writer::writers::{PageLabel, Resources};
-use pdf_writer::{Content, Filter, Finish, Name, Rect, Ref, Str, TextStr};
-use typst::introspection::Meta;
+use pdf_writer::{Content, Filter, Finish, Name, Primitive, Rect, Ref, Str, TextStr};
+use ttf_parser::{GlyphId, RasterImageFormat};
+use typst::foundations::{Packed, StyleChain};
+use typst::introspection::{Location, Meta};
 use typst::layout::{
     Abs, Em, Frame, FrameItem, GroupItem, Page, Point, Ratio, Size, Transform,
 };
-use typst::model::{Destination, Numbering};
+use typst::model::{Destination, HeadingElem, Numbering};
+use typst::syntax::Span;
 use typst::text::{Case, Font, TextItem};
 use typst::util::{Deferred, Numeric};
 use typst::visualize::{
-    FixedStroke, Geometry, Image, LineCap, LineJoin, Paint, Path, PathItem, Shape,
+    Color, ConicGradient, FixedStroke, Geometry, Gradient, Image, LineCap, LineJoin,
+    LinearGradient, Paint, Path, PathItem, RadialGradient, RasterFormat, Rgb, Shape,
 };
+use xmp_writer::XmpWriter;
 
 use crate::color::PaintEncode;
 use crate::extg::ExtGState;
 use crate::image::deferred_image;
-use crate::{deflate_deferred, AbsExt, EmExt, PdfContext};
+use crate::{
+    deflate_deferred, modified_date, write_piece_info, xmp_date, AbsExt, EmExt,
+    ModelFormat, PdfContext, PdfModel, TextEncoding,
+};
 
 /// Construct page objects.
 #[typst_macros::time(name = "construct pages")]
@@ -53,6 +62,9 @@ pub(crate) fn construct_page(ctx: &mut PdfContext, frame: &Frame) -> (Ref, Encod
         saves: vec![],
         bottom: 0.0,
         links: vec![],
+        last_link: None,
+        marked: vec![],
+        marking: None,
         resources: HashMap::default(),
     };
 
@@ -70,12 +82,18 @@ pub(crate) fn construct_page(ctx: &mut PdfContext, frame: &Frame) -> (Ref, Encod
     // Encode the page into the content stream.
     write_frame(&mut ctx, frame);
 
+    let content = ctx.content.finish();
     let page = EncodedPage {
         size,
-        content: deflate_deferred(ctx.content.finish()),
+        content: if ctx.parent.options.uncompressed {
+            Deferred::new(move || content)
+        } else {
+            deflate_deferred(content)
+        },
         id: ctx.page_ref,
         uses_opacities: ctx.uses_opacities,
         links: ctx.links,
+        marked: ctx.marked,
         label: None,
         resources: ctx.resources,
     };
@@ -157,6 +175,7 @@ fn write_global_resources(ctx: &mut PdfContext) -> Ref {
 fn write_page(ctx: &mut PdfContext, i: usize, resources_ref: Ref) {
     let page = &ctx.pages[i];
     let content_id = ctx.alloc.bump();
+    let modified = modified_date(ctx);
 
     let mut page_writer = ctx.pdf.page(page.id);
     page_writer.parent(ctx.page_tree_ref);
@@ -167,6 +186,31 @@ fn write_page(ctx: &mut PdfContext, i: usize, resources_ref: Ref) {
     page_writer.contents(content_id);
     page_writer.pair(Name(b"Resources"), resources_ref);
 
+    // The page's marked content is mapped back to the structure tree through
+    // the parent tree entry with the page's index.
+    if !page.marked.is_empty() {
+        page_writer.pair(Name(b"StructParents"), i as i32);
+    }
+
+    let metadata = ctx.options.page_metadata.iter().find(|meta| meta.page.get() - 1 == i);
+    let metadata_ref = metadata.map(|_| ctx.alloc.bump());
+    if let Some(metadata_ref) = metadata_ref {
+        page_writer.pair(Name(b"Metadata"), metadata_ref);
+    }
+
+    // A page with application data must also state when it was last
+    // modified, so the data is only written if that is known.
+    let pieces: Vec<_> = ctx
+        .options
+        .piece_info
+        .iter()
+        .filter(|piece| piece.page.is_some_and(|page| page.get() - 1 == i))
+        .collect();
+    if let Some(modified) = modified.filter(|_| !pieces.is_empty()) {
+        page_writer.pair(Name(b"LastModified"), modified);
+        write_piece_info(&mut page_writer, pieces, modified);
+    }
+
     if page.uses_opacities {
         page_writer
             .group()
@@ -178,11 +222,18 @@ fn write_page(ctx: &mut PdfContext, i: usize, resources_ref: Ref) {
     }
 
     let mut annotations = page_writer.annotations();
-    for (dest, rect) in &page.links {
+    for (dest, rect, quads) in &page.links {
         let mut annotation = annotations.push();
         annotation.subtype(AnnotationType::Link).rect(*rect);
         annotation.border(0.0, 0.0, 0.0, None).flags(AnnotationFlags::PRINT);
 
+        // A link that is split into multiple segments (e.g. because it wraps
+        // across lines) gets one quadrilateral per segment, so that only the
+        // segments themselves are clickable and not the whole bounding box.
+        if quads.len() > 1 {
+            annotation.quad_points(quads.iter().flatten().copied());
+        }
+
         let pos = match dest {
             Destination::Url(uri) => {
                 annotation
@@ -219,14 +270,142 @@ fn write_page(ctx: &mut PdfContext, i: usize, resources_ref: Ref) {
         }
     }
 
+    let mut appearances = vec![];
+    for note in &ctx.options.notes {
+        if note.pos.page.get() - 1 != i {
+            continue;
+        }
+
+        let x = note.pos.point.x.to_f32();
+        let y = (page.size.y - note.pos.point.y).to_f32();
+        let mut annotation = annotations.push();
+        annotation
+            .subtype(AnnotationType::Text)
+            .rect(Rect::new(x, y - NOTE_SIZE, x + NOTE_SIZE, y))
+            .contents(TextStr(&note.contents))
+            .flags(AnnotationFlags::PRINT | AnnotationFlags::NO_ZOOM);
+
+        // Viewers are free to draw text annotations however they like unless
+        // there is an explicit appearance stream, so we provide one.
+        let appearance_id = ctx.alloc.bump();
+        annotation.insert(Name(b"AP")).dict().pair(Name(b"N"), appearance_id);
+        appearances.push((appearance_id, note_appearance(note.color)));
+
+        if let Some(author) = &note.author {
+            annotation.author(TextStr(author));
+        }
+
+        if let Some(color) = note.color {
+            let [r, g, b, _] = color.to_rgb().to_vec4();
+            annotation.color_rgb(r, g, b);
+        }
+    }
+
+    let mut models = vec![];
+    for model in &ctx.options.models {
+        if model.pos.page.get() - 1 != i {
+            continue;
+        }
+
+        let x = model.pos.point.x.to_f32();
+        let y = (page.size.y - model.pos.point.y).to_f32();
+        let (w, h) = (model.size.x.to_f32(), model.size.y.to_f32());
+        let stream_id = ctx.alloc.bump();
+        let view_id = ctx.alloc.bump();
+        let mut annotation = annotations.push();
+        annotation
+            .rect(Rect::new(x, y - h, x + w, y))
+            .flags(AnnotationFlags::PRINT)
+            .pair(Name(b"Subtype"), Name(b"3D"))
+            .pair(Name(b"3DD"), stream_id)
+            .pair(Name(b"3DV"), view_id);
+        models.push((stream_id, view_id, model));
+    }
+
     annotations.finish();
     page_writer.finish();
 
-    ctx.pdf
-        .stream(content_id, page.content.wait())
-        .filter(Filter::FlateDecode);
+    if let Some((meta, metadata_ref)) = metadata.zip(metadata_ref) {
+        let mut xmp = XmpWriter::new();
+        xmp.source(&meta.source);
+        if let Some(date) = meta.date.and_then(|date| xmp_date(date, true)) {
+            xmp.create_date(date);
+        }
+
+        let xmp_buf = xmp.finish(None);
+        ctx.pdf
+            .stream(metadata_ref, xmp_buf.as_bytes())
+            .pair(Name(b"Type"), Name(b"Metadata"))
+            .pair(Name(b"Subtype"), Name(b"XML"));
+    }
+
+    for (id, appearance) in appearances {
+        ctx.pdf
+            .form_xobject(id, &appearance)
+            .bbox(Rect::new(0.0, 0.0, NOTE_SIZE, NOTE_SIZE));
+    }
+
+    for (stream_id, view_id, model) in models {
+        write_model(ctx, stream_id, view_id, model);
+    }
+
+    let mut stream = ctx.pdf.stream(content_id, page.content.wait());
+    if !ctx.options.uncompressed {
+        stream.filter(Filter::FlateDecode);
+    }
+}
+
+/// Create the normal appearance of a sticky note: a filled square with a few
+/// lines hinting at text.
+fn note_appearance(color: Option<Color>) -> Vec<u8> {
+    let [r, g, b, _] = color.unwrap_or(Color::YELLOW).to_rgb().to_vec4();
+    let mut content = Content::new();
+    content.set_fill_rgb(r, g, b);
+    content.set_stroke_gray(0.0);
+    content.set_line_width(1.0);
+    content.rect(0.5, 0.5, NOTE_SIZE - 1.0, NOTE_SIZE - 1.0);
+    content.fill_nonzero_and_stroke();
+    for y in [6.0, 10.0, 14.0] {
+        content.move_to(4.0, y);
+        content.line_to(NOTE_SIZE - 4.0, y);
+    }
+    content.stroke();
+    content.finish()
 }
 
+/// Write a 3D model's stream and its default view.
+fn write_model(ctx: &mut PdfContext, stream_id: Ref, view_id: Ref, model: &PdfModel) {
+    let subtype = match model.format {
+        ModelFormat::U3d => Name(b"U3D"),
+        ModelFormat::Prc => Name(b"PRC"),
+    };
+
+    let mut stream = ctx.pdf.stream(stream_id, &model.data);
+    stream.pair(Name(b"Type"), Name(b"3D"));
+    stream.pair(Name(b"Subtype"), subtype);
+    stream.insert(Name(b"VA")).array().item(view_id);
+    stream.pair(Name(b"DV"), 0);
+    stream.finish();
+
+    // The default view looks at the origin from the front, such that the
+    // model's x-axis points right and its z-axis points up.
+    let mut view = ctx.pdf.indirect(view_id).dict();
+    view.pair(Name(b"Type"), Name(b"3DView"));
+    view.pair(Name(b"XN"), TextStr("Default"));
+    view.pair(Name(b"MS"), Name(b"M"));
+    // The camera's x-, y- and z-axes followed by its position.
+    let c2w =
+        [[1.0, 0.0, 0.0], [0.0, 0.0, -1.0], [0.0, 1.0, 0.0], [0.0, -MODEL_DISTANCE, 0.0]];
+    view.insert(Name(b"C2W")).array().items(c2w.into_iter().flatten());
+    view.pair(Name(b"CO"), MODEL_DISTANCE);
+}
+
+/// The distance of a 3D model's default camera from the model's origin.
+const MODEL_DISTANCE: f32 = 100.0;
+
+/// The width and height of a sticky note's icon.
+const NOTE_SIZE: f32 = 20.0;
+
 /// Write the page labels.
 pub(crate) fn write_page_labels(ctx: &mut PdfContext) -> Vec<(NonZeroUsize, Ref)> {
     let mut result = vec![];
@@ -363,8 +542,12 @@ pub struct EncodedPage {
     pub content: Deferred<Vec<u8>>,
     /// Whether the page uses opacities.
     pub uses_opacities: bool,
-    /// Links in the PDF coordinate system.
-    pub links: Vec<(Destination, Rect)>,
+    /// Links in the PDF coordinate system, with their bounding boxes and the
+    /// quadrilaterals of their individual segments.
+    pub links: Vec<(Destination, Rect, Vec<[f32; 8]>)>,
+    /// The elements that the page's marked-content sequences belong to,
+    /// indexed by their marked-content identifier.
+    pub marked: Vec<Location>,
     /// The page's used resources
     pub resources: HashMap<PageResource, usize>,
     /// The page's PDF label.
@@ -435,7 +618,13 @@ pub struct PageContext<'a, 'b> {
     saves: Vec<State>,
     bottom: f32,
     uses_opacities: bool,
-    links: Vec<(Destination, Rect)>,
+    links: Vec<(Destination, Rect, Vec<[f32; 8]>)>,
+    /// The span of the link that the last entry in `links` belongs to.
+    last_link: Option<Span>,
+    /// The elements of the marked-content sequences written so far.
+    marked: Vec<Location>,
+    /// The element whose marked-content sequence is currently open, if any.
+    marking: Option<Location>,
     /// Keep track of the resources being used in the page.
     pub resources: HashMap<PageResource, usize>,
 }
@@ -525,6 +714,11 @@ impl PageContext<'_, '_> {
     }
 
     fn set_opacities(&mut self, stroke: Option<&FixedStroke>, fill: Option<&Paint>) {
+        // Flattened paints are always opaque.
+        if self.parent.options.flatten {
+            return;
+        }
+
         let stroke_opacity = stroke
             .map(|stroke| {
                 let color = match &stroke.paint {
@@ -585,6 +779,14 @@ impl PageContext<'_, '_> {
     }
 
     fn set_fill(&mut self, fill: &Paint, on_text: bool, transforms: Transforms) {
+        let flattened;
+        let fill = if self.parent.options.flatten {
+            flattened = flatten_paint(fill);
+            &flattened
+        } else {
+            fill
+        };
+
         if self.state.fill.as_ref() != Some(fill)
             || matches!(self.state.fill, Some(Paint::Gradient(_)))
         {
@@ -610,6 +812,17 @@ impl PageContext<'_, '_> {
         on_text: bool,
         transforms: Transforms,
     ) {
+        let flattened;
+        let stroke = if self.parent.options.flatten {
+            flattened = FixedStroke {
+                paint: flatten_paint(&stroke.paint),
+                ..stroke.clone()
+            };
+            &flattened
+        } else {
+            stroke
+        };
+
         if self.state.stroke.as_ref() != Some(stroke)
             || matches!(
                 self.state.stroke.as_ref().map(|s| &s.paint),
@@ -653,11 +866,87 @@ impl PageContext<'_, '_> {
     pub fn reset_stroke_color_space(&mut self) {
         self.state.stroke_space = None;
     }
+
+    /// Opens a marked-content sequence for the heading if the document is
+    /// tagged and no sequence is open. Returns whether a sequence was opened.
+    fn begin_marked_content(&mut self, heading: &Packed<HeadingElem>) -> bool {
+        if !self.parent.options.tagged || self.marking.is_some() {
+            return false;
+        }
+
+        let Some(loc) = heading.location() else { return false };
+        let level = heading.resolve_level(StyleChain::default()).get().min(6);
+        let tag = eco_format!("H{level}");
+        let mcid = self.marked.len() as i32;
+        self.content
+            .begin_marked_content_with_properties(Name(tag.as_bytes()))
+            .properties()
+            .identify(mcid);
+        self.marked.push(loc);
+        self.marking = Some(loc);
+        true
+    }
+
+    /// Closes the currently open marked-content sequence.
+    fn end_marked_content(&mut self) {
+        self.content.end_marked_content();
+        self.marking = None;
+    }
 }
 
 /// Encode a frame into the content stream.
 fn write_frame(ctx: &mut PageContext, frame: &Frame) {
+    // A frame that was laid out for a heading starts with the heading's
+    // metadata, so all of its content belongs to the heading.
+    if let Some(heading) = heading_of(frame) {
+        let opened = ctx.begin_marked_content(heading);
+        write_items(ctx, frame, false);
+        if opened {
+            ctx.end_marked_content();
+        }
+    } else {
+        write_items(ctx, frame, true);
+    }
+}
+
+/// Encode the items of a frame into the content stream.
+///
+/// When `mark` is set, items that were inlined from a heading's frame are
+/// wrapped in a marked-content sequence. Such an item directly follows the
+/// metadata with the heading's location. Subsequent items of the same
+/// heading share one sequence.
+fn write_items(ctx: &mut PageContext, frame: &Frame, mark: bool) {
+    // The heading whose metadata precedes the next content item.
+    let mut pending: Option<&Packed<HeadingElem>> = None;
+    // Whether this frame opened the current marked-content sequence.
+    let mut opened = false;
+
     for &(pos, ref item) in frame.items() {
+        if let FrameItem::Meta(meta, size) = item {
+            match meta {
+                Meta::Link(dest, span) => write_link(ctx, pos, dest, *span, *size),
+                Meta::Elem(elem) => {
+                    if let Some(heading) = elem.to_packed::<HeadingElem>() {
+                        pending = Some(heading);
+                    }
+                }
+                Meta::Hide => {}
+            }
+            continue;
+        }
+
+        if mark {
+            let owner = pending.take();
+            let loc = owner.and_then(|heading| heading.location());
+            if opened && ctx.marking != loc {
+                ctx.end_marked_content();
+                opened = false;
+            }
+            if let Some(heading) = owner {
+                opened |= ctx.begin_marked_content(heading);
+            }
+        }
+
         let x = pos.x.to_f32();
         let y = pos.y.to_f32();
         match item {
@@ -665,13 +954,33 @@ fn write_frame(ctx: &mut PageContext, frame: &Frame) {
             FrameItem::Text(text) => write_text(ctx, pos, text),
             FrameItem::Shape(shape, _) => write_shape(ctx, pos, shape),
             FrameItem::Image(image, size, _) => write_image(ctx, x, y, image, *size),
-            FrameItem::Meta(meta, size) => match meta {
-                Meta::Link(dest) => write_link(ctx, pos, dest, *size),
-                Meta::Elem(_) => {}
-                Meta::Hide => {}
-            },
+            FrameItem::Meta(..) => {}
+        }
+    }
+
+    if opened {
+        ctx.end_marked_content();
+    }
+}
+
+/// The heading that the frame was laid out for, if any.
+///
+/// Layout prepends an element's metadata to the frames produced for it,
+/// covering the whole frame.
+fn heading_of(frame: &Frame) -> Option<&Packed<HeadingElem>> {
+    let mut items = frame.items().map(|(_, item)| item).peekable();
+    let mut heading = None;
+    while let Some(FrameItem::Meta(meta, size)) = items.peek() {
+        if let Meta::Elem(elem) = meta {
+            if *size == frame.size() {
+                heading = heading.or(elem.to_packed::<HeadingElem>());
+            }
         }
+        items.next();
     }
+
+    // Frames that only carry metadata have no content to mark.
+    items.next().and(heading)
 }
 
 /// Encode a group into the content stream.
@@ -718,32 +1027,44 @@ fn write_text(ctx: &mut PageContext, pos: Point, text: &TextItem) {
     ctx.set_fill(&text.fill, true, fill_transform);
     if let Some(stroke) = &text.stroke {
         ctx.set_stroke(stroke, true, fill_transform);
-        ctx.content
-            .set_text_rendering_mode(pdf_writer::types::TextRenderingMode::FillStroke);
+        ctx.content.set_text_rendering_mode(TextRenderingMode::FillStroke);
     }
     ctx.set_font(&text.font, text.size);
     ctx.set_opacities(text.stroke.as_ref(), Some(&text.fill));
+
+    // When outlining, the glyphs are drawn as paths and the text itself is
+    // only written invisibly to keep it extractable. Runs with layered color
+    // glyphs are always outlined since PDF fonts can't draw their layers.
+    let outline = ctx.parent.options.outline_text || has_layered_glyphs(text);
+    if outline {
+        write_outlines(ctx, pos, text, fill_transform);
+        ctx.content.set_text_rendering_mode(TextRenderingMode::Invisible);
+    }
+
     ctx.content.begin_text();
 
     // Position the text.
     ctx.content.set_text_matrix([1.0, 0.0, 0.0, -1.0, x, y]);
 
-    let mut positioned = ctx.content.show_positioned();
-    let mut items = positioned.items();
+    let encoding = ctx.parent.options.text_encoding;
+    let mut positioned = ctx.content.op("TJ");
+    let mut items = positioned.obj().array();
     let mut adjustment = Em::zero();
     let mut encoded = vec![];
 
-    // Write the glyphs with kerning adjustments.
+    // Write the glyphs with kerning adjustments. The glyphs are already in
+    // visual order, also in right-to-left runs, so they are always written
+    // from left to right and advance the text position by their own width.
     for glyph in &text.glyphs {
         adjustment += glyph.x_offset;
 
         if !adjustment.is_zero() {
             if !encoded.is_empty() {
-                items.show(Str(&encoded));
+                items.item(EncodedStr(&encoded, encoding));
                 encoded.clear();
             }
 
-            items.adjust(-adjustment.to_font_units());
+            items.item(-adjustment.to_font_units());
             adjustment = Em::zero();
         }
 
@@ -759,12 +1080,290 @@ fn write_text(ctx: &mut PageContext, pos: Point, text: &TextItem) {
     }
 
     if !encoded.is_empty() {
-        items.show(Str(&encoded));
+        items.item(EncodedStr(&encoded, encoding));
     }
 
     items.finish();
     positioned.finish();
     ctx.content.end_text();
+
+    if outline {
+        ctx.content.set_text_rendering_mode(match text.stroke {
+            Some(_) => TextRenderingMode::FillStroke,
+            None => TextRenderingMode::Fill,
+        });
+    }
+
+    write_bitmap_glyphs(ctx, pos, text);
+}
+
+/// Draw color glyphs that only exist as bitmaps, like emoji from `sbix` or
+/// `CBDT` fonts, as images on top of the text. The text itself draws nothing
+/// for these glyphs as they have no outlines, but keeps them extractable.
+fn write_bitmap_glyphs(ctx: &mut PageContext, pos: Point, text: &TextItem) {
+    let tables = text.font.ttf().tables();
+    if tables.sbix.is_none() && tables.cbdt.is_none() {
+        return;
+    }
+
+    let mut x = pos.x;
+    for glyph in &text.glyphs {
+        let offset = x + glyph.x_offset.at(text.size);
+        x += glyph.x_advance.at(text.size);
+
+        let Some(bitmap) = bitmap_glyph(&text.font, glyph.id) else { continue };
+
+        // The bitmap's placement is given in pixels relative to the glyph's
+        // origin on the baseline, with the y-axis pointing up.
+        let px = text.size / bitmap.ppem;
+        let size = Size::new(px * bitmap.image.width(), px * bitmap.image.height());
+        let left = offset + px * bitmap.x;
+        let top = pos.y - px * bitmap.y - size.y;
+        write_image(ctx, left.to_f32(), top.to_f32(), &bitmap.image, size);
+    }
+}
+
+/// A color glyph's bitmap.
+#[derive(Clone, Hash)]
+struct BitmapGlyph {
+    /// The decoded PNG image.
+    image: Image,
+    /// The horizontal offset of the image from the glyph origin in pixels.
+    x: f64,
+    /// The vertical offset of the image's bottom from the baseline in pixels.
+    y: f64,
+    /// The number of pixels per em the bitmap was designed for.
+    ppem: f64,
+}
+
+/// Extract the bitmap of a glyph without outlines, if it has one.
+#[comemo::memoize]
+fn bitmap_glyph(font: &Font, id: u16) -> Option<BitmapGlyph> {
+    let ttf = font.ttf();
+    if ttf.glyph_bounding_box(GlyphId(id)).is_some() {
+        return None;
+    }
+
+    let raster = ttf.glyph_raster_image(GlyphId(id), u16::MAX)?;
+    if raster.format != RasterImageFormat::PNG || raster.pixels_per_em == 0 {
+        return None;
+    }
+
+    let image = Image::new(raster.data.into(), RasterFormat::Png.into(), None).ok()?;
+    Some(BitmapGlyph {
+        image,
+        x: raster.x.into(),
+        y: raster.y.into(),
+        ppem: raster.pixels_per_em.into(),
+    })
+}
+
+/// Whether a text run contains color glyphs made of layers from a `COLR`
+/// table.
+fn has_layered_glyphs(text: &TextItem) -> bool {
+    let ttf = text.font.ttf();
+    ttf.tables().colr.is_some()
+        && text.glyphs.iter().any(|glyph| ttf.is_color_glyph(GlyphId(glyph.id)))
+}
+
+/// Draw the outlines of a text run's glyphs as paths.
+fn write_outlines(
+    ctx: &mut PageContext,
+    pos: Point,
+    text: &TextItem,
+    fill_transform: Transforms,
+) {
+    let scale = (text.size / text.font.units_per_em()).to_f32();
+    let mut x = pos.x;
+    for glyph in &text.glyphs {
+        let offset = x + glyph.x_offset.at(text.size);
+        let origin = (offset.to_f32(), pos.y.to_f32());
+        x += glyph.x_advance.at(text.size);
+
+        let id = GlyphId(glyph.id);
+        if text.font.ttf().is_color_glyph(id) {
+            write_color_layers(ctx, origin, scale, text, id, fill_transform);
+            continue;
+        }
+
+        let mut builder = OutlineWriter {
+            content: &mut ctx.content,
+            origin,
+            scale,
+            last: (0.0, 0.0),
+        };
+        if text.font.ttf().outline_glyph(id, &mut builder).is_some() {
+            match text.stroke {
+                Some(_) => ctx.content.fill_nonzero_and_stroke(),
+                None => ctx.content.fill_nonzero(),
+            };
+        }
+    }
+}
+
+/// Draw a color glyph from a `COLR` table as its layers, each filled with
+/// its color from the font's first `CPAL` palette. Layers that use the
+/// foreground color are filled with the text's fill.
+fn write_color_layers(
+    ctx: &mut PageContext,
+    origin: (f32, f32),
+    scale: f32,
+    text: &TextItem,
+    id: GlyphId,
+    fill_transform: Transforms,
+) {
+    let ttf = text.font.ttf();
+    let mut painter = LayerPainter { glyph: id, layers: vec![] };
+    if ttf.paint_color_glyph(id, 0, &mut painter).is_none() {
+        return;
+    }
+
+    ctx.save_state();
+    for (glyph, color) in painter.layers {
+        let fill = color.map_or_else(|| text.fill.clone(), Paint::Solid);
+        ctx.set_fill(&fill, true, fill_transform);
+        ctx.set_opacities(None, Some(&fill));
+
+        let mut builder = OutlineWriter {
+            content: &mut ctx.content,
+            origin,
+            scale,
+            last: (0.0, 0.0),
+        };
+        if ttf.outline_glyph(glyph, &mut builder).is_some() {
+            ctx.content.fill_nonzero();
+        }
+    }
+    ctx.restore_state();
+}
+
+/// Collects the layers of a color glyph from a `COLR` table.
+struct LayerPainter {
+    /// The glyph whose outline the next layer is filled in.
+    glyph: GlyphId,
+    /// The layers from bottom to top, with their color or `None` for the
+    /// foreground color.
+    layers: Vec<(GlyphId, Option<Color>)>,
+}
+
+impl ttf_parser::colr::Painter for LayerPainter {
+    fn outline(&mut self, glyph_id: GlyphId) {
+        self.glyph = glyph_id;
+    }
+
+    fn paint_foreground(&mut self) {
+        self.layers.push((self.glyph, None));
+    }
+
+    fn paint_color(&mut self, color: ttf_parser::RgbaColor) {
+        let color = Color::from_u8(color.red, color.green, color.blue, color.alpha);
+        self.layers.push((self.glyph, Some(color)));
+    }
+}
+
+/// Writes glyph outlines in font units into a content stream.
+struct OutlineWriter<'a> {
+    content: &'a mut Content,
+    /// The position of the glyph's origin on the page.
+    origin: (f32, f32),
+    /// The size of one font unit.
+    scale: f32,
+    /// The last point, in font units. Needed to convert quadratic curves,
+    /// which PDF doesn't support, into cubic ones.
+    last: (f32, f32),
+}
+
+impl OutlineWriter<'_> {
+    /// Convert a point from font units into page coordinates.
+    fn point(&self, x: f32, y: f32) -> (f32, f32) {
+        (self.origin.0 + x * self.scale, self.origin.1 - y * self.scale)
+    }
+}
+
+impl ttf_parser::OutlineBuilder for OutlineWriter<'_> {
+    fn move_to(&mut self, x: f32, y: f32) {
+        let (px, py) = self.point(x, y);
+        self.content.move_to(px, py);
+        self.last = (x, y);
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        let (px, py) = self.point(x, y);
+        self.content.line_to(px, py);
+        self.last = (x, y);
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        let (x0, y0) = self.last;
+        self.curve_to(
+            x0 + 2.0 / 3.0 * (x1 - x0),
+            y0 + 2.0 / 3.0 * (y1 - y0),
+            x + 2.0 / 3.0 * (x1 - x),
+            y + 2.0 / 3.0 * (y1 - y),
+            x,
+            y,
+        );
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        let (px1, py1) = self.point(x1, y1);
+        let (px2, py2) = self.point(x2, y2);
+        let (px, py) = self.point(x, y);
+        self.content.cubic_to(px1, py1, px2, py2, px, py);
+        self.last = (x, y);
+    }
+
+    fn close(&mut self) {
+        self.content.close_path();
+    }
+}
+
+/// A string in a text showing operator, written with a specific encoding.
+struct EncodedStr<'a>(&'a [u8], TextEncoding);
+
+impl Primitive for EncodedStr<'_> {
+    fn write(self, buf: &mut Vec<u8>) {
+        let EncodedStr(bytes, encoding) = self;
+        let encoding = match encoding {
+            TextEncoding::Auto if literal_len(bytes) <= 2 * bytes.len() => {
+                TextEncoding::Literal
+            }
+            TextEncoding::Auto => TextEncoding::Hex,
+            encoding => encoding,
+        };
+
+        if encoding == TextEncoding::Hex {
+            buf.push(b'<');
+            for &byte in bytes {
+                buf.extend(format!("{byte:02X}").as_bytes());
+            }
+            buf.push(b'>');
+            return;
+        }
+
+        buf.push(b'(');
+        for &byte in bytes {
+            match byte {
+                b'(' | b')' | b'\\' => buf.extend([b'\\', byte]),
+                0x20..=0x7E => buf.push(byte),
+                _ => buf.extend(format!("\\{byte:03o}").as_bytes()),
+            }
+        }
+        buf.push(b')');
+    }
+}
+
+/// The length of the contents of a literal string (without the enclosing
+/// parentheses) for the given bytes.
+fn literal_len(bytes: &[u8]) -> usize {
+    bytes
+        .iter()
+        .map(|byte| match byte {
+            b'(' | b')' | b'\\' => 2,
+            0x20..=0x7E => 1,
+            _ => 4,
+        })
+        .sum()
 }
 
 /// Encode a geometrical shape into the content stream.
@@ -848,13 +1447,61 @@ fn write_path(ctx: &mut PageContext, x: f32, y: f32, path: &Path) {
     }
 }
 
+/// Composites a translucent paint over white, making it opaque.
+fn flatten_paint(paint: &Paint) -> Paint {
+    match paint {
+        Paint::Solid(color) => Paint::Solid(flatten_color(*color)),
+        Paint::Gradient(gradient) => Paint::Gradient(flatten_gradient(gradient)),
+        // The pattern's content is flattened when its frame is written.
+        Paint::Pattern(_) => paint.clone(),
+    }
+}
+
+/// Composites each translucent stop of a gradient over white.
+fn flatten_gradient(gradient: &Gradient) -> Gradient {
+    let flatten = |stops: &[(Color, Ratio)]| -> Vec<(Color, Ratio)> {
+        stops
+            .iter()
+            .map(|&(color, offset)| (flatten_color(color), offset))
+            .collect()
+    };
+
+    match gradient {
+        Gradient::Linear(linear) => {
+            let stops = flatten(&linear.stops);
+            Gradient::Linear(Arc::new(LinearGradient { stops, ..(**linear).clone() }))
+        }
+        Gradient::Radial(radial) => {
+            let stops = flatten(&radial.stops);
+            Gradient::Radial(Arc::new(RadialGradient { stops, ..(**radial).clone() }))
+        }
+        Gradient::Conic(conic) => {
+            let stops = flatten(&conic.stops);
+            Gradient::Conic(Arc::new(ConicGradient { stops, ..(**conic).clone() }))
+        }
+    }
+}
+
+/// Composites a translucent color over white, making it opaque.
+fn flatten_color(color: Color) -> Color {
+    match color.alpha() {
+        Some(alpha) if alpha < 1.0 => {
+            let [r, g, b, _] = color.to_rgb().to_vec4();
+            let over_white = |c: f32| c * alpha + (1.0 - alpha);
+            Color::Rgb(Rgb::new(over_white(r), over_white(g), over_white(b), 1.0))
+        }
+        _ => color,
+    }
+}
+
 /// Encode a vector or raster image into the content stream.
 fn write_image(ctx: &mut PageContext, x: f32, y: f32, image: &Image, size: Size) {
     let index = ctx.parent.image_map.insert(image.clone());
+    let flatten = ctx.parent.options.flatten;
     ctx.parent
         .image_deferred_map
         .entry(index)
-        .or_insert_with(|| deferred_image(image.clone()));
+        .or_insert_with(|| deferred_image(image.clone(), flatten));
 
     let name = eco_format!("Im{index}");
     let w = size.x.to_f32();
@@ -882,20 +1529,34 @@ fn write_image(ctx: &mut PageContext, x: f32, y: f32, image: &Image, size: Size)
 }
 
 /// Save a link for later writing in the annotations dictionary.
-fn write_link(ctx: &mut PageContext, pos: Point, dest: &Destination, size: Size) {
+///
+/// Consecutive pieces of the same link (e.g. the lines of a link that wraps
+/// across lines) are merged into a single annotation. Pieces belong to the
+/// same link if they have the same destination and the same, attached span.
+fn write_link(
+    ctx: &mut PageContext,
+    pos: Point,
+    dest: &Destination,
+    span: Span,
+    size: Size,
+) {
     let mut min_x = Abs::inf();
     let mut min_y = Abs::inf();
     let mut max_x = -Abs::inf();
     let mut max_y = -Abs::inf();
 
-    // Compute the bounding box of the transformed link.
-    for point in [
-        pos,
-        pos + Point::with_x(size.x),
+    // Compute the transformed corners of the link in counterclockwise order,
+    // starting at the bottom left (in the PDF coordinate system).
+    let corners = [
         pos + Point::with_y(size.y),
         pos + size.to_point(),
-    ] {
-        let t = point.transform(ctx.state.transform);
+        pos + Point::with_x(size.x),
+        pos,
+    ]
+    .map(|point| point.transform(ctx.state.transform));
+
+    // Compute the bounding box of the transformed link.
+    for t in corners {
         min_x.set_min(t.x);
         min_y.set_min(t.y);
         max_x.set_max(t.x);
@@ -908,7 +1569,28 @@ fn write_link(ctx: &mut PageContext, pos: Point, dest: &Destination, size: Size)
     let y2 = min_y.to_f32();
     let rect = Rect::new(x1, y1, x2, y2);
 
-    ctx.links.push((dest.clone(), rect));
+    let mut quad = [0.0; 8];
+    for (i, corner) in corners.iter().enumerate() {
+        quad[2 * i] = corner.x.to_f32();
+        quad[2 * i + 1] = corner.y.to_f32();
+    }
+
+    let same_link = !span.is_detached() && ctx.last_link == Some(span);
+    if let Some((prev, bbox, quads)) = ctx.links.last_mut() {
+        if same_link && prev == dest {
+            *bbox = Rect::new(
+                bbox.x1.min(rect.x1),
+                bbox.y1.max(rect.y1),
+                bbox.x2.max(rect.x2),
+                bbox.y2.min(rect.y2),
+            );
+            quads.push(quad);
+            return;
+        }
+    }
+
+    ctx.links.push((dest.clone(), rect, vec![quad]));
+    ctx.last_link = Some(span);
 }
 
 fn to_pdf_line_cap(cap: LineCap) -> LineCapStyle {
@@ -936,3 +1618,549 @@ fn to_pdf_numbering_style(style: PdfPageLabelStyle) -> NumberingStyle {
         PdfPageLabelStyle::UpperAlpha => NumberingStyle::UpperAlpha,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use std::num::NonZeroUsize;
+    use std::sync::Arc;
+
+    use pdf_writer::Primitive;
+    use ttf_parser::GlyphId;
+    use typst::foundations::{Bytes, Datetime, Smart};
+    use typst::introspection::Meta;
+    use typst::layout::{
+        Abs, Angle, Em, Frame, FrameItem, Page, Point, Position, Ratio, Size,
+    };
+    use typst::model::{Destination, Document};
+    use typst::syntax::{Source, Span};
+    use typst::text::{Font, Glyph, Lang, TextItem};
+    use typst::visualize::{
+        Color, ColorSpace, Geometry, Gradient, Image, ImageFormat, LinearGradient, Paint,
+        RasterFormat, Rgb,
+    };
+
+    use super::{flatten_paint, EncodedStr};
+    use crate::{
+        ModelFormat, PdfModel, PdfNote, PdfOptions, PdfPageMetadata, PdfPieceInfo,
+        TextEncoding,
+    };
+
+    fn pt(v: f64) -> Abs {
+        Abs::pt(v)
+    }
+
+    /// An empty 100pt × 100pt frame to draw test content into.
+    fn canvas() -> Frame {
+        Frame::soft(Size::new(pt(100.0), pt(100.0)))
+    }
+
+    /// A document with one page per frame.
+    fn document(frames: impl IntoIterator<Item = Frame>) -> Document {
+        let pages = frames
+            .into_iter()
+            .zip(1..)
+            .map(|(frame, number)| Page { frame, numbering: None, number })
+            .collect();
+        Document { pages, ..Default::default() }
+    }
+
+    /// The first of the bundled fonts.
+    fn font() -> Font {
+        typst_assets::fonts()
+            .find_map(|data| Font::new(Bytes::from_static(data), 0))
+            .unwrap()
+    }
+
+    /// A 10pt black text run with one glyph per character of `text`, in
+    /// logical order.
+    fn text_run(font: &Font, text: &str) -> TextItem {
+        let glyphs = text
+            .char_indices()
+            .map(|(i, c)| {
+                let id = font.ttf().glyph_index(c).unwrap().0;
+                Glyph {
+                    id,
+                    x_advance: font.advance(id).unwrap(),
+                    x_offset: Em::zero(),
+                    range: i as u16..(i + c.len_utf8()) as u16,
+                    span: (Span::detached(), 0),
+                }
+            })
+            .collect();
+        TextItem {
+            font: font.clone(),
+            size: pt(10.0),
+            fill: Paint::Solid(Color::BLACK),
+            stroke: None,
+            lang: Lang::ENGLISH,
+            text: text.into(),
+            glyphs,
+        }
+    }
+
+    /// The ids of the page objects, in order.
+    fn page_ids(pdf: &str) -> Vec<&str> {
+        let (_, kids) = pdf.split_once("/Kids [").unwrap();
+        let (kids, _) = kids.split_once(']').unwrap();
+        kids.split(" 0 R").map(str::trim).collect()
+    }
+
+    /// Distinct, attached spans, as they would belong to different links.
+    fn spans() -> Vec<Span> {
+        let source = Source::detached("a b c");
+        source.root().children().map(|node| node.span()).collect()
+    }
+
+    #[test]
+    fn test_wrapped_link_has_quad_points() {
+        let mut frame = canvas();
+        let dest = Destination::Url("https://typst.app".into());
+        let span = spans()[0];
+        for (pos, size) in [
+            (Point::new(pt(10.0), pt(10.0)), Size::new(pt(50.0), pt(10.0))),
+            (Point::new(pt(10.0), pt(30.0)), Size::new(pt(30.0), pt(10.0))),
+        ] {
+            frame.push(pos, FrameItem::Meta(Meta::Link(dest.clone(), span), size));
+        }
+
+        let document = document([frame]);
+        let pdf = crate::pdf(&document, None, None);
+        let pdf = String::from_utf8_lossy(&pdf);
+
+        assert_eq!(pdf.matches("/Subtype /Link").count(), 1);
+        assert!(pdf.contains("/Rect [10 90 60 60]"));
+        assert!(
+            pdf.contains("/QuadPoints [10 80 60 80 60 90 10 90 10 60 40 60 40 70 10 70]")
+        );
+    }
+
+    #[test]
+    fn test_link_targets() {
+        let mut frame = canvas();
+        let spans = spans();
+        let size = Size::new(pt(50.0), pt(10.0));
+        let url = Destination::Url("https://typst.app".into());
+        frame.push(
+            Point::new(pt(10.0), pt(10.0)),
+            FrameItem::Meta(Meta::Link(url, spans[0]), size),
+        );
+        let pos = Destination::Position(Position {
+            page: NonZeroUsize::new(2).unwrap(),
+            point: Point::new(pt(20.0), pt(40.0)),
+        });
+        frame.push(
+            Point::new(pt(10.0), pt(30.0)),
+            FrameItem::Meta(Meta::Link(pos, spans[1]), size),
+        );
+
+        let document = document([frame, canvas()]);
+        let pdf = crate::pdf(&document, None, None);
+        let pdf = String::from_utf8_lossy(&pdf);
+
+        // Both rectangles are flipped into PDF coordinates.
+        let kids = page_ids(&pdf);
+        let page = object(&pdf, kids[0]);
+        assert_eq!(page.matches("/Subtype /Link").count(), 2);
+        assert!(page.contains("/Rect [10 90 60 80]"));
+        assert!(page.contains("/URI (https://typst.app)"));
+
+        // The internal link jumps to the second page, slightly above the
+        // target position.
+        assert!(page.contains("/Rect [10 70 60 60]"));
+        assert!(page.contains(&format!("/D [{} 0 R /XYZ 20 70 null]", kids[1])));
+    }
+
+    #[test]
+    fn test_adjacent_links_stay_separate() {
+        let mut frame = canvas();
+        let dest = Destination::Url("https://typst.app".into());
+        let size = Size::new(pt(20.0), pt(10.0));
+        for (i, span) in spans().into_iter().enumerate().take(2) {
+            let pos = Point::new(pt(10.0 + 30.0 * i as f64), pt(10.0));
+            frame.push(pos, FrameItem::Meta(Meta::Link(dest.clone(), span), size));
+        }
+
+        let document = document([frame]);
+        let pdf = crate::pdf(&document, None, None);
+        let pdf = String::from_utf8_lossy(&pdf);
+
+        assert_eq!(pdf.matches("/Subtype /Link").count(), 2);
+        assert!(!pdf.contains("/QuadPoints"));
+    }
+
+    #[test]
+    fn test_sticky_note() {
+        let document = document([canvas()]);
+        let note = PdfNote {
+            pos: Position {
+                page: NonZeroUsize::ONE,
+                point: Point::new(pt(30.0), pt(20.0)),
+            },
+            contents: "Fix this".into(),
+            author: Some("Alice".into()),
+            color: Some(Color::from_u8(255, 0, 0, 255)),
+        };
+        let options = PdfOptions::default().with_notes(vec![note]);
+        let pdf = crate::pdf_with_options(&document, &options);
+        let pdf = String::from_utf8_lossy(&pdf);
+
+        assert_eq!(pdf.matches("/Subtype /Text").count(), 1);
+        assert!(pdf.contains("/Rect [30 60 50 80]"));
+        assert!(pdf.contains("/Contents (Fix this)"));
+        assert!(pdf.contains("/T (Alice)"));
+        assert!(pdf.contains("/C [1 0 0]"));
+
+        let (_, rest) = pdf.split_once("/AP <<\n").unwrap();
+        let (_, rest) = rest.split_once("/N ").unwrap();
+        let (id, _) = rest.split_once(' ').unwrap();
+        let (_, xobject) = pdf.split_once(&format!("\n{id} 0 obj")).unwrap();
+        let (xobject, _) = xobject.split_once("endobj").unwrap();
+        assert!(xobject.contains("/Type /XObject"));
+        assert!(xobject.contains("/Subtype /Form"));
+        assert!(xobject.contains("/BBox [0 0 20 20]"));
+        assert!(xobject.contains("1 0 0 rg"));
+    }
+
+    /// Find all streams in a PDF file and decompress those that are
+    /// compressed.
+    fn streams(pdf: &[u8]) -> Vec<String> {
+        fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+            haystack.windows(needle.len()).position(|w| w == needle)
+        }
+
+        let mut streams = vec![];
+        let mut rest = pdf;
+        while let Some(start) = find(rest, b"stream\n") {
+            rest = &rest[start + b"stream\n".len()..];
+            let end = find(rest, b"\nendstream").unwrap();
+            let data = &rest[..end];
+            let data = miniz_oxide::inflate::decompress_to_vec_zlib(data)
+                .unwrap_or_else(|_| data.to_vec());
+            streams.push(String::from_utf8_lossy(&data).into_owned());
+            rest = &rest[end + b"\nendstream".len()..];
+        }
+        streams
+    }
+
+    #[test]
+    fn test_outlined_text_stays_extractable() {
+        let text = text_run(&font(), "Hi");
+        let mut frame = canvas();
+        frame.push(Point::new(pt(10.0), pt(20.0)), FrameItem::Text(text));
+        let document = document([frame]);
+        let options = PdfOptions::default().with_outline_text(true);
+        let streams = streams(&crate::pdf_with_options(&document, &options));
+
+        // The glyphs are drawn as filled paths below invisible text.
+        let content = streams.iter().find(|s| s.contains("TJ")).unwrap();
+        let outlines = content.find("\nf\n").unwrap();
+        let invisible = content.find("3 Tr").unwrap();
+        assert!(outlines < invisible);
+        assert!(invisible < content.find("TJ").unwrap());
+
+        // The invisible text maps back to the source string.
+        let cmap = streams.iter().find(|s| s.contains("beginbfchar")).unwrap();
+        let cmap = cmap.to_uppercase();
+        assert!(cmap.contains("<0048>"));
+        assert!(cmap.contains("<0069>"));
+    }
+
+    #[test]
+    fn test_rtl_run_keeps_visual_order() {
+        let font = font();
+
+        // Glyphs of a right-to-left run are stored in visual order, so their
+        // text ranges are descending.
+        let ltr = text_run(&font, "ab");
+        let mut rtl = text_run(&font, "xyz");
+        rtl.glyphs.reverse();
+        let rtl_x = pt(10.0) + ltr.width();
+        let mut frame = canvas();
+        frame.push(Point::new(pt(10.0), pt(20.0)), FrameItem::Text(ltr));
+        frame.push(Point::new(rtl_x, pt(20.0)), FrameItem::Text(rtl));
+        let document = document([frame]);
+        let options = PdfOptions::default().with_text_encoding(TextEncoding::Hex);
+        let streams = streams(&crate::pdf_with_options(&document, &options));
+        let content = streams.iter().find(|s| s.contains("TJ")).unwrap();
+
+        // Each run is written in visual order without any adjustments that
+        // could make glyphs overlap.
+        let hex = |text: &str| -> String {
+            text.chars()
+                .map(|c| format!("{:04X}", font.ttf().glyph_index(c).unwrap().0))
+                .collect()
+        };
+        let runs: Vec<_> = content.lines().filter(|l| l.ends_with("TJ")).collect();
+        let ab = format!("[<{}>] TJ", hex("ab"));
+        let zyx = format!("[<{}>] TJ", hex("zyx"));
+        assert_eq!(runs, [ab, zyx]);
+
+        // The right-to-left run starts where the left-to-right one ends.
+        let starts: Vec<f32> = content
+            .lines()
+            .filter_map(|l| l.strip_suffix(" Tm"))
+            .map(|l| l.split(' ').nth(4).unwrap().parse().unwrap())
+            .collect();
+        assert_eq!(starts.len(), 2);
+        assert!((starts[0] - 10.0).abs() < 1e-3);
+        assert!((starts[1] - rtl_x.to_pt() as f32).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_bitmap_emoji_is_drawn_as_image() {
+        let (font, id) = typst_dev_assets::fonts()
+            .flat_map(|data| Font::iter(Bytes::from_static(data)))
+            .find_map(|font| {
+                let id = font.ttf().glyph_index('😀')?.0;
+                super::bitmap_glyph(&font, id)?;
+                Some((font, id))
+            })
+            .unwrap();
+
+        let text = text_run(&font, "😀");
+        assert_eq!(text.glyphs[0].id, id);
+
+        let mut frame = canvas();
+        frame.push(Point::new(pt(10.0), pt(20.0)), FrameItem::Text(text));
+        let document = document([frame]);
+        let pdf = crate::pdf(&document, None, None);
+        let streams = streams(&pdf);
+
+        // The bitmap is embedded as an image and drawn after the text, which
+        // stays extractable.
+        let pdf = String::from_utf8_lossy(&pdf);
+        assert!(pdf.contains("/Subtype /Image"));
+        let content = streams.iter().find(|s| s.contains("TJ")).unwrap();
+        assert!(content.find("TJ").unwrap() < content.find("/Im0 Do").unwrap());
+    }
+
+    #[test]
+    fn test_colr_glyph_is_drawn_as_layers() {
+        let (font, id) = typst_assets::fonts()
+            .chain(typst_dev_assets::fonts())
+            .flat_map(|data| Font::iter(Bytes::from_static(data)))
+            .find_map(|font| {
+                let ttf = font.ttf();
+                ttf.tables().colr?;
+                let id = (0..ttf.number_of_glyphs())
+                    .find(|&id| ttf.is_color_glyph(GlyphId(id)))?;
+                Some((font, id))
+            })
+            .unwrap();
+
+        let mut text = text_run(&font, "x");
+        text.glyphs[0].id = id;
+
+        let mut frame = canvas();
+        frame.push(Point::new(pt(10.0), pt(20.0)), FrameItem::Text(text));
+        let document = document([frame]);
+        let streams = streams(&crate::pdf(&document, None, None));
+        let content = streams.iter().find(|s| s.contains("TJ")).unwrap();
+
+        // The layers are filled in their own colors below the text, which is
+        // invisible and only keeps the glyph extractable.
+        let fills = content.lines().filter(|l| *l == "f").count();
+        let mut colors: Vec<_> = content.lines().filter(|l| l.ends_with(" sc")).collect();
+        colors.sort_unstable();
+        colors.dedup();
+        assert!(fills >= 2);
+        assert!(colors.len() >= 2);
+        assert!(content.rfind("\nf\n").unwrap() < content.find("3 Tr").unwrap());
+    }
+
+    /// Find the indirect object with the given id.
+    fn object<'a>(pdf: &'a str, id: &str) -> &'a str {
+        let (_, rest) = pdf.split_once(&format!("\n{id} 0 obj\n")).unwrap();
+        rest.split_once("endobj").unwrap().0
+    }
+
+    #[test]
+    fn test_page_metadata() {
+        let document = document([canvas(), canvas()]);
+        let meta = PdfPageMetadata {
+            page: NonZeroUsize::new(2).unwrap(),
+            source: "chapters/intro.typ".into(),
+            date: None,
+        };
+        let options = PdfOptions::default().with_page_metadata(vec![meta]);
+        let pdf = crate::pdf_with_options(&document, &options);
+        let pdf = String::from_utf8_lossy(&pdf);
+
+        let kids = page_ids(&pdf);
+
+        // Only the second page has its own metadata.
+        assert!(!object(&pdf, kids[0]).contains("/Metadata"));
+        let (_, rest) = object(&pdf, kids[1]).split_once("/Metadata ").unwrap();
+        let (id, _) = rest.split_once(' ').unwrap();
+        let metadata = object(&pdf, id);
+        assert!(metadata.contains("/Type /Metadata"));
+        assert!(metadata.contains("chapters/intro.typ"));
+    }
+
+    #[test]
+    fn test_piece_info() {
+        let document = document([canvas(), canvas()]);
+        let piece = |page: Option<usize>, app: &str, data: &[u8]| PdfPieceInfo {
+            page: page.and_then(NonZeroUsize::new),
+            app: app.into(),
+            data: data.to_vec(),
+        };
+        let options = PdfOptions::default()
+            .with_timestamp(Datetime::from_ymd_hms(2024, 1, 2, 3, 4, 5))
+            .with_piece_info(vec![
+                piece(None, "Editor", b"doc"),
+                piece(Some(2), "Editor", b"old"),
+                piece(Some(2), "Editor", b"grid 3x2"),
+            ]);
+        let pdf = crate::pdf_with_options(&document, &options);
+        let pdf = String::from_utf8_lossy(&pdf);
+
+        // Read the private data back by application name.
+        let private = |dict: &str| -> Option<String> {
+            let (_, piece_info) = dict.split_once("/PieceInfo")?;
+            let (_, entry) = piece_info.split_once("/Editor")?;
+            let (_, data) = entry.split_once("/Private (")?;
+            Some(data.split_once(')')?.0.to_string())
+        };
+
+        let kids = page_ids(&pdf);
+        assert_eq!(private(object(&pdf, kids[0])), None);
+        assert_eq!(private(object(&pdf, kids[1])).as_deref(), Some("grid 3x2"));
+        assert!(object(&pdf, kids[1]).contains("/LastModified (D:20240102030405"));
+
+        let (_, catalog) = pdf.split_once("/Type /Catalog").unwrap();
+        assert_eq!(private(catalog).as_deref(), Some("doc"));
+
+        // Without any date, there is no modification date to write alongside
+        // the data, so it is left out.
+        let options = options.with_timestamp(None);
+        let pdf = crate::pdf_with_options(&document, &options);
+        let pdf = String::from_utf8_lossy(&pdf);
+        assert!(!pdf.contains("/PieceInfo"));
+        assert!(!pdf.contains("/LastModified"));
+    }
+
+    #[test]
+    fn test_flatten() {
+        let mut png = Cursor::new(vec![]);
+        image::RgbaImage::from_pixel(2, 2, image::Rgba([0, 0, 255, 128]))
+            .write_to(&mut png, image::ImageFormat::Png)
+            .unwrap();
+        let format = ImageFormat::Raster(RasterFormat::Png);
+        let image = Image::new(Bytes::from(png.into_inner()), format, None).unwrap();
+
+        let fill = Paint::Solid(Color::Rgb(Rgb::new(1.0, 0.0, 0.0, 0.5)));
+        let size = Size::new(pt(20.0), pt(20.0));
+        let shape = Geometry::Rect(size).filled(fill);
+        let mut frame = canvas();
+        frame.push(Point::zero(), FrameItem::Shape(shape, Span::detached()));
+        frame.push(Point::zero(), FrameItem::Image(image, size, Span::detached()));
+        let document = document([frame]);
+
+        let pdf = crate::pdf(&document, None, None);
+        let pdf = String::from_utf8_lossy(&pdf);
+        assert!(pdf.contains("/Group"));
+        assert!(pdf.contains("/SMask"));
+        assert!(pdf.contains("/ca 0.5"));
+
+        // The same content is drawn with opaque colors that look the same on
+        // the white page.
+        let options = PdfOptions::default().with_flatten(true);
+        let pdf = crate::pdf_with_options(&document, &options);
+        let streams = streams(&pdf);
+        let pdf = String::from_utf8_lossy(&pdf);
+        for construct in ["/Group", "/SMask", "/ca ", "/CA ", "/OCProperties"] {
+            assert!(!pdf.contains(construct), "flattened PDF contains {construct}");
+        }
+        assert!(pdf.contains("/Subtype /Image"));
+        assert!(streams.iter().any(|s| s.contains("1 0.5 0.5 sc")));
+
+        // Gradients are flattened stop by stop.
+        let gradient = Gradient::Linear(Arc::new(LinearGradient {
+            stops: vec![
+                (Color::Rgb(Rgb::new(0.0, 0.0, 1.0, 0.5)), Ratio::zero()),
+                (Color::BLACK, Ratio::one()),
+            ],
+            angle: Angle::zero(),
+            space: ColorSpace::Oklab,
+            relative: Smart::Auto,
+            anti_alias: true,
+        }));
+        let Paint::Gradient(flattened) = flatten_paint(&Paint::Gradient(gradient)) else {
+            panic!("flattened gradient is no gradient");
+        };
+        let [r, g, b, a] = flattened.stops_ref()[0].0.to_rgb().to_vec4();
+        assert_eq!([r, g, b, a], [0.5, 0.5, 1.0, 1.0]);
+        assert_eq!(flattened.stops_ref()[1].0, Color::BLACK);
+    }
+
+    #[test]
+    fn test_uncompressed_content() {
+        let mut frame = canvas();
+        for i in 0..100 {
+            let size = Size::new(pt(1.0), pt(1.0));
+            let shape = Geometry::Rect(size).filled(Paint::Solid(Color::BLACK));
+            let pos = Point::new(pt(f64::from(i % 10)), pt(f64::from(i / 10)));
+            frame.push(pos, FrameItem::Shape(shape, Span::detached()));
+        }
+        let document = document([frame]);
+
+        let compressed = crate::pdf(&document, None, None);
+        let options = PdfOptions::default().with_uncompressed(true);
+        let uncompressed = crate::pdf_with_options(&document, &options);
+        assert!(compressed.len() < uncompressed.len());
+        assert_eq!(streams(&compressed), streams(&uncompressed));
+
+        // The drawing operators appear as-is in the file.
+        assert!(String::from_utf8_lossy(&uncompressed).contains(" re\n"));
+    }
+
+    #[test]
+    fn test_model_annotation() {
+        let document = document([canvas()]);
+        let model = PdfModel {
+            pos: Position {
+                page: NonZeroUsize::ONE,
+                point: Point::new(pt(10.0), pt(20.0)),
+            },
+            size: Size::new(pt(60.0), pt(40.0)),
+            format: ModelFormat::U3d,
+            data: b"U3D model".to_vec(),
+        };
+        let options = PdfOptions::default().with_models(vec![model]);
+        let pdf = crate::pdf_with_options(&document, &options);
+        let pdf = String::from_utf8_lossy(&pdf);
+
+        let (_, annotation) = pdf.split_once("/Subtype /3D\n").unwrap();
+        assert!(pdf.contains("/Rect [10 40 70 80]"));
+        let reference = |key: &str| {
+            let (_, rest) = annotation.split_once(key).unwrap();
+            rest.trim_start().split_once(' ').unwrap().0.to_string()
+        };
+
+        let stream = object(&pdf, &reference("/3DD"));
+        assert!(stream.contains("/Type /3D\n"));
+        assert!(stream.contains("/Subtype /U3D"));
+        assert!(stream.contains("U3D model"));
+
+        let view = reference("/3DV");
+        assert!(stream.contains(&format!("/VA [{view} 0 R]")));
+        assert!(object(&pdf, &view).contains("/Type /3DView"));
+    }
+
+    fn encode(bytes: &[u8], encoding: TextEncoding) -> String {
+        let mut buf = vec![];
+        EncodedStr(bytes, encoding).write(&mut buf);
+        String::from_utf8(buf).unwrap()
+    }
+
+    #[test]
+    fn test_text_encoding() {
+        let bytes = b"a(\\\x01";
+        assert_eq!(encode(bytes, TextEncoding::Literal), r"(a\(\\\001)");
+        assert_eq!(encode(bytes, TextEncoding::Hex), "<61285C01>");
+        assert_eq!(encode(bytes, TextEncoding::Auto), "<61285C01>");
+        assert_eq!(encode(b"a)", TextEncoding::Auto), r"(a\))");
+    }
+}
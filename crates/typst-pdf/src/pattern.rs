@@ -71,11 +71,12 @@ pub(crate) fn write_patterns(ctx: &mut PdfContext) {
         );
 
         resources_map.finish();
-        tiling_pattern
-            .matrix(transform_to_array(
-                transform.pre_concat(Transform::scale(Ratio::one(), -Ratio::one())),
-            ))
-            .filter(Filter::FlateDecode);
+        tiling_pattern.matrix(transform_to_array(
+            transform.pre_concat(Transform::scale(Ratio::one(), -Ratio::one())),
+        ));
+        if !ctx.options.uncompressed {
+            tiling_pattern.filter(Filter::FlateDecode);
+        }
     }
 }
 
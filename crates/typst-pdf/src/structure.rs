@@ -0,0 +1,190 @@
+use std::collections::HashMap;
+
+use pdf_writer::{Finish, Name, Ref, TextStr};
+use typst::foundations::{NativeElement, StyleChain};
+use typst::introspection::Location;
+use typst::model::HeadingElem;
+
+use crate::PdfContext;
+
+/// Write the logical structure tree of the document.
+///
+/// Currently, the tree consists of a `Document` element whose children are
+/// the document's headings, tagged as `H1` to `H6` depending on their level.
+/// Each heading refers to the marked-content sequences that make up its
+/// content on the pages and the parent tree maps them back to the heading.
+pub(crate) fn write_structure_tree(ctx: &mut PdfContext) -> Ref {
+    let root_ref = ctx.alloc.bump();
+    let document_ref = ctx.alloc.bump();
+
+    // Find the marked-content sequences of each element.
+    let mut sequences: HashMap<Location, Vec<(usize, i32)>> = HashMap::new();
+    for (i, page) in ctx.pages.iter().enumerate() {
+        for (mcid, &loc) in page.marked.iter().enumerate() {
+            sequences.entry(loc).or_default().push((i, mcid as i32));
+        }
+    }
+
+    let mut kids = vec![];
+    let mut refs = HashMap::new();
+    let elements = ctx.document.introspector.query(&HeadingElem::elem().select());
+    for elem in elements.iter() {
+        let heading = elem.to_packed::<HeadingElem>().unwrap();
+        let level = heading.resolve_level(StyleChain::default()).get().min(6);
+        let tag = format!("H{level}");
+
+        let id = ctx.alloc.bump();
+        let mut element = ctx.pdf.indirect(id).dict();
+        element.pair(Name(b"Type"), Name(b"StructElem"));
+        element.pair(Name(b"S"), Name(tag.as_bytes()));
+        element.pair(Name(b"P"), document_ref);
+        element.pair(Name(b"T"), TextStr(heading.body().plain_text().trim()));
+
+        let loc = heading.location().unwrap();
+        let index = ctx.document.introspector.page(loc).get() - 1;
+        if let Some(&page_ref) = ctx.page_refs.get(index) {
+            element.pair(Name(b"Pg"), page_ref);
+        }
+
+        if let Some(parts) = sequences.get(&loc) {
+            let mut children = element.insert(Name(b"K")).array();
+            for &(i, mcid) in parts {
+                let mut reference = children.push().dict();
+                reference.pair(Name(b"Type"), Name(b"MCR"));
+                reference.pair(Name(b"Pg"), ctx.page_refs[i]);
+                reference.pair(Name(b"MCID"), mcid);
+            }
+        }
+
+        element.finish();
+        refs.insert(loc, id);
+        kids.push(id);
+    }
+
+    let mut document = ctx.pdf.indirect(document_ref).dict();
+    document.pair(Name(b"Type"), Name(b"StructElem"));
+    document.pair(Name(b"S"), Name(b"Document"));
+    document.pair(Name(b"P"), root_ref);
+    document.insert(Name(b"K")).array().items(kids);
+    document.finish();
+
+    let mut root = ctx.pdf.indirect(root_ref).dict();
+    root.pair(Name(b"Type"), Name(b"StructTreeRoot"));
+    root.pair(Name(b"K"), document_ref);
+
+    // Map each page's marked-content identifiers back to their elements. The
+    // pages use their index as the key, see `/StructParents`.
+    let mut parent_tree = root.insert(Name(b"ParentTree")).dict();
+    let mut nums = parent_tree.insert(Name(b"Nums")).array();
+    for (i, page) in ctx.pages.iter().enumerate() {
+        if !page.marked.is_empty() {
+            nums.item(i as i32);
+            nums.push().array().items(page.marked.iter().map(|loc| refs[loc]));
+        }
+    }
+    nums.finish();
+    parent_tree.finish();
+
+    root.pair(Name(b"ParentTreeNextKey"), ctx.pages.len() as i32);
+    root.finish();
+
+    root_ref
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroUsize;
+
+    use typst::foundations::{NativeElement, Smart};
+    use typst::introspection::{Location, Meta};
+    use typst::layout::{Abs, Frame, FrameItem, Geometry, GroupItem, Page, Point, Size};
+    use typst::model::{Document, HeadingElem};
+    use typst::syntax::Span;
+    use typst::text::TextElem;
+    use typst::visualize::Color;
+
+    use crate::PdfOptions;
+
+    #[test]
+    fn test_headings_are_tagged_with_level() {
+        let level = NonZeroUsize::new(2).unwrap();
+        let mut heading = HeadingElem::new(TextElem::packed("Intro"))
+            .with_level(Smart::Custom(level))
+            .pack();
+        heading.set_location(Location { hash: 1, disambiguator: 0, variant: 0 });
+
+        // The heading's metadata precedes the first square, but not the second
+        // one, even though that one lies within the heading's area.
+        let area = Size::new(Abs::pt(100.0), Abs::pt(20.0));
+        let square =
+            Geometry::Rect(Size::splat(Abs::pt(10.0))).filled(Color::BLACK.into());
+        let mut frame = Frame::soft(Size::splat(Abs::pt(100.0)));
+        frame.push(Point::zero(), FrameItem::Meta(Meta::Elem(heading), area));
+        frame.push(Point::zero(), FrameItem::Shape(square.clone(), Span::detached()));
+        let beside = Point::with_x(Abs::pt(50.0));
+        frame.push(beside, FrameItem::Shape(square, Span::detached()));
+        let pages = vec![Page { frame, numbering: None, number: 1 }];
+        let mut document = Document { pages, ..Default::default() };
+        document.introspector.rebuild(&document.pages);
+
+        let options = PdfOptions::default().with_tagged(true).with_uncompressed(true);
+        let pdf = crate::pdf_with_options(&document, &options);
+        let pdf = String::from_utf8_lossy(&pdf);
+
+        assert!(pdf.contains("/StructTreeRoot"));
+        assert!(pdf.contains("/S /H2"));
+        assert!(pdf.contains("/T (Intro)"));
+
+        // Only the headings are tagged, so the document doesn't claim to be
+        // a tagged PDF.
+        assert!(!pdf.contains("/Marked true"));
+        assert!(!pdf.contains("pdfuaid:part"));
+
+        // The first square is wrapped in a marked-content sequence that is
+        // linked to the heading in both directions.
+        let begin = pdf.find("BDC").unwrap();
+        let end = pdf.find("EMC").unwrap();
+        assert_eq!(pdf.matches(" re\n").count(), 2);
+        assert_eq!(pdf[begin..end].matches(" re\n").count(), 1);
+        assert!(pdf[begin - 20..begin].contains("/H2 "));
+        assert!(pdf.contains("/MCID 0"));
+        assert!(pdf.contains("/Type /MCR"));
+        assert!(pdf.contains("/StructParents 0"));
+        assert!(pdf.contains("/ParentTree"));
+    }
+
+    #[test]
+    fn test_heading_frames_are_tagged_whole() {
+        let mut heading = HeadingElem::new(TextElem::packed("Intro")).pack();
+        heading.set_location(Location { hash: 1, disambiguator: 0, variant: 0 });
+
+        // The heading's frame carries its metadata, so both squares in it
+        // belong to the heading, while the square after it does not.
+        let square =
+            Geometry::Rect(Size::splat(Abs::pt(10.0))).filled(Color::BLACK.into());
+        let mut inner = Frame::soft(Size::new(Abs::pt(100.0), Abs::pt(20.0)));
+        inner.push(Point::zero(), FrameItem::Shape(square.clone(), Span::detached()));
+        let beside = Point::with_x(Abs::pt(50.0));
+        inner.push(beside, FrameItem::Shape(square.clone(), Span::detached()));
+        inner.meta_iter([Meta::Elem(heading)]);
+
+        let mut frame = Frame::soft(Size::splat(Abs::pt(100.0)));
+        frame.push(Point::zero(), FrameItem::Group(GroupItem::new(inner)));
+        let below = Point::with_y(Abs::pt(50.0));
+        frame.push(below, FrameItem::Shape(square, Span::detached()));
+        let pages = vec![Page { frame, numbering: None, number: 1 }];
+        let mut document = Document { pages, ..Default::default() };
+        document.introspector.rebuild(&document.pages);
+
+        let options = PdfOptions::default().with_tagged(true).with_uncompressed(true);
+        let pdf = crate::pdf_with_options(&document, &options);
+        let pdf = String::from_utf8_lossy(&pdf);
+
+        let begin = pdf.find("BDC").unwrap();
+        let end = pdf.find("EMC").unwrap();
+        assert_eq!(pdf.matches("BDC").count(), 1);
+        assert_eq!(pdf.matches(" re\n").count(), 3);
+        assert_eq!(pdf[begin..end].matches(" re\n").count(), 2);
+        assert!(pdf[begin - 20..begin].contains("/H1 "));
+    }
+}
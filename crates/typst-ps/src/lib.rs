@@ -0,0 +1,810 @@
+//! Exporting of Typst documents into PostScript files.
+//!
+//! The exported files target PostScript Level 2 and follow the Document
+//! Structuring Conventions, so that they can be fed into legacy printing
+//! pipelines. TrueType fonts are subsetted to the used glyphs and embedded as
+//! Type 42 fonts, all other fonts are drawn as filled glyph outlines.
+//!
+//! PostScript has no transparency, so translucent image pixels are composited
+//! over white. SVG images, gradients, patterns and text strokes cannot be
+//! expressed and are reported as errors.
+
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::Write;
+
+use ecow::{eco_format, EcoString};
+use ttf_parser::{GlyphId, OutlineBuilder};
+use typst::diag::{bail, SourceResult};
+use typst::layout::{Abs, Frame, FrameItem, GroupItem, Point, Size, Transform};
+use typst::model::Document;
+use typst::syntax::Span;
+use typst::text::{Font, TextItem};
+use typst::visualize::{
+    Color, FixedStroke, Geometry, Image, ImageKind, LineCap, LineJoin, Paint, Path,
+    PathItem, Shape,
+};
+
+/// The maximum length of a PostScript string.
+///
+/// The Type 42 specification requires one extra padding byte at the end of
+/// each string in the `sfnts` array, so we leave room for that.
+const MAX_STRING_LEN: usize = 65534;
+
+/// The number of bytes written per line of hexadecimal data.
+const HEX_LINE_LEN: usize = 40;
+
+/// Export a document into a PostScript file.
+///
+/// Returns the raw bytes making up the PostScript file or an error if the
+/// document contains content that PostScript cannot express.
+#[typst_macros::time(name = "ps")]
+pub fn ps(document: &Document) -> SourceResult<Vec<u8>> {
+    check(document)?;
+
+    let mut ctx = PsContext::new(document);
+    ctx.write_header();
+    ctx.write_fonts();
+    ctx.write_pages();
+    ctx.buf.push_str("%%Trailer\n%%EOF\n");
+    Ok(ctx.buf.into_bytes())
+}
+
+/// Ensure that a document only contains content that can be exported into
+/// PostScript.
+pub fn check(document: &Document) -> SourceResult<()> {
+    for page in &document.pages {
+        check_frame(&page.frame)?;
+    }
+    Ok(())
+}
+
+/// Ensure that a frame only contains content that can be exported.
+fn check_frame(frame: &Frame) -> SourceResult<()> {
+    for (_, item) in frame.items() {
+        match item {
+            FrameItem::Group(group) => check_frame(&group.frame)?,
+            FrameItem::Text(text) => {
+                let span = text.glyphs.first().map_or(Span::detached(), |g| g.span.0);
+                check_paint(&text.fill, span)?;
+                if text.stroke.is_some() {
+                    bail!(
+                        span, "PostScript export does not support text strokes";
+                        hint: "try exporting to PDF instead"
+                    );
+                }
+            }
+            FrameItem::Shape(shape, span) => {
+                if let Some(fill) = &shape.fill {
+                    check_paint(fill, *span)?;
+                }
+                if let Some(stroke) = &shape.stroke {
+                    check_paint(&stroke.paint, *span)?;
+                }
+            }
+            FrameItem::Image(image, _, span) => {
+                if !matches!(image.kind(), ImageKind::Raster(_)) {
+                    bail!(
+                        *span, "PostScript export does not support SVG images";
+                        hint: "try converting the image to PNG or exporting to PDF instead"
+                    );
+                }
+            }
+            FrameItem::Meta(..) => {}
+        }
+    }
+    Ok(())
+}
+
+/// Ensure that a paint can be exported.
+fn check_paint(paint: &Paint, span: Span) -> SourceResult<()> {
+    match paint {
+        Paint::Solid(_) => Ok(()),
+        Paint::Gradient(_) => bail!(
+            span, "PostScript export does not support gradients";
+            hint: "try exporting to PDF instead"
+        ),
+        Paint::Pattern(_) => bail!(
+            span, "PostScript export does not support patterns";
+            hint: "try exporting to PDF instead"
+        ),
+    }
+}
+
+/// Context for exporting a whole PostScript document.
+struct PsContext<'a> {
+    /// The document that we're currently exporting.
+    document: &'a Document,
+    /// The output buffer.
+    buf: String,
+    /// The Type 42 fonts used in the document alongside the glyphs used from
+    /// each of them, in order of first use.
+    fonts: Vec<(Font, BTreeSet<u16>)>,
+    /// Maps fonts to their index in `fonts`.
+    font_map: HashMap<Font, usize>,
+}
+
+impl<'a> PsContext<'a> {
+    fn new(document: &'a Document) -> Self {
+        let mut ctx = Self {
+            document,
+            buf: String::new(),
+            fonts: vec![],
+            font_map: HashMap::new(),
+        };
+        for page in &document.pages {
+            ctx.collect_fonts(&page.frame);
+        }
+        ctx
+    }
+
+    /// Collect the Type 42 fonts and glyphs used in a frame.
+    fn collect_fonts(&mut self, frame: &Frame) {
+        for (_, item) in frame.items() {
+            match item {
+                FrameItem::Group(group) => self.collect_fonts(&group.frame),
+                FrameItem::Text(text) if is_type42(&text.font) => {
+                    let index =
+                        *self.font_map.entry(text.font.clone()).or_insert_with(|| {
+                            self.fonts.push((text.font.clone(), BTreeSet::new()));
+                            self.fonts.len() - 1
+                        });
+                    self.fonts[index].1.extend(text.glyphs.iter().map(|g| g.id));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Write the DSC header comments.
+    fn write_header(&mut self) {
+        let (mut width, mut height) = (0.0_f64, 0.0_f64);
+        for page in &self.document.pages {
+            width = width.max(page.frame.width().to_pt().ceil());
+            height = height.max(page.frame.height().to_pt().ceil());
+        }
+
+        self.buf.push_str("%!PS-Adobe-3.0\n");
+        let _ = writeln!(self.buf, "%%Creator: Typst {}", env!("CARGO_PKG_VERSION"));
+        if let Some(title) = &self.document.title {
+            let _ = writeln!(self.buf, "%%Title: {}", string(title));
+        }
+        let _ = writeln!(self.buf, "%%Pages: {}", self.document.pages.len());
+        let _ = writeln!(self.buf, "%%BoundingBox: 0 0 {width} {height}");
+        self.buf.push_str("%%LanguageLevel: 2\n");
+        self.buf.push_str("%%EndComments\n");
+    }
+
+    /// Embed all Type 42 fonts into the document setup.
+    fn write_fonts(&mut self) {
+        self.buf.push_str("%%BeginSetup\n");
+        for (i, (font, glyphs)) in self.fonts.iter().enumerate() {
+            write_type42(&mut self.buf, i, font, glyphs);
+        }
+        self.buf.push_str("%%EndSetup\n");
+    }
+
+    /// Write all pages of the document.
+    fn write_pages(&mut self) {
+        for (i, page) in self.document.pages.iter().enumerate() {
+            let size = page.frame.size();
+            let number = i + 1;
+            let _ = writeln!(self.buf, "%%Page: {number} {number}");
+            let _ = writeln!(
+                self.buf,
+                "%%PageBoundingBox: 0 0 {} {}",
+                size.x.to_pt().ceil(),
+                size.y.to_pt().ceil(),
+            );
+            self.buf.push_str("%%BeginPageSetup\n");
+            let _ = writeln!(
+                self.buf,
+                "<< /PageSize [{} {}] >> setpagedevice",
+                num(size.x),
+                num(size.y),
+            );
+            self.buf.push_str("%%EndPageSetup\n");
+
+            // Flip the coordinate system so that we can use Typst's
+            // top-left origin for everything.
+            self.buf.push_str("save\n");
+            let _ = writeln!(self.buf, "0 {} translate 1 -1 scale", num(size.y));
+            self.write_frame(&page.frame);
+            self.buf.push_str("restore\nshowpage\n");
+        }
+    }
+
+    /// Write a frame's contents.
+    fn write_frame(&mut self, frame: &Frame) {
+        for (pos, item) in frame.items() {
+            self.buf.push_str("gsave\n");
+            if *pos != Point::zero() {
+                let _ = writeln!(self.buf, "{} {} translate", num(pos.x), num(pos.y));
+            }
+            match item {
+                FrameItem::Group(group) => self.write_group(group),
+                FrameItem::Text(text) => self.write_text(text),
+                FrameItem::Shape(shape, _) => self.write_shape(shape),
+                FrameItem::Image(image, size, _) => self.write_image(image, *size),
+                FrameItem::Meta(..) => {}
+            }
+            self.buf.push_str("grestore\n");
+        }
+    }
+
+    /// Write a group with its transform and clip path.
+    fn write_group(&mut self, group: &GroupItem) {
+        if !group.transform.is_identity() {
+            let Transform { sx, ky, kx, sy, tx, ty } = group.transform;
+            let _ = writeln!(
+                self.buf,
+                "[{} {} {} {} {} {}] concat",
+                sx.get() as f32,
+                ky.get() as f32,
+                kx.get() as f32,
+                sy.get() as f32,
+                num(tx),
+                num(ty),
+            );
+        }
+
+        if let Some(clip_path) = &group.clip_path {
+            let _ = writeln!(self.buf, "newpath {} clip newpath", path(clip_path));
+        }
+
+        self.write_frame(&group.frame);
+    }
+
+    /// Write a text run.
+    fn write_text(&mut self, text: &TextItem) {
+        let Some(color) = paint_color(&text.fill) else { return };
+        write_color(&mut self.buf, color);
+
+        // Glyphs are drawn upright, so we flip the coordinate system back.
+        self.buf.push_str("1 -1 scale\n");
+
+        if let Some(&index) = self.font_map.get(&text.font) {
+            let _ = writeln!(self.buf, "/F{index} {} selectfont", num(text.size));
+            let mut x = Abs::zero();
+            for glyph in &text.glyphs {
+                let offset = x + glyph.x_offset.at(text.size);
+                let name = glyph_name(glyph.id);
+                let _ = writeln!(self.buf, "{} 0 moveto /{name} glyphshow", num(offset));
+                x += glyph.x_advance.at(text.size);
+            }
+        } else {
+            let scale = text.size.to_pt() / text.font.units_per_em();
+            let mut x = Abs::zero();
+            for glyph in &text.glyphs {
+                let offset = x + glyph.x_offset.at(text.size);
+                x += glyph.x_advance.at(text.size);
+
+                let mut writer = OutlineWriter { buf: String::new(), last: (0.0, 0.0) };
+                let id = GlyphId(glyph.id);
+                if text.font.ttf().outline_glyph(id, &mut writer).is_none() {
+                    continue;
+                }
+
+                let _ = writeln!(
+                    self.buf,
+                    "gsave {} 0 translate {scale} {scale} scale newpath\n{}fill grestore",
+                    num(offset),
+                    writer.buf,
+                );
+            }
+        }
+    }
+
+    /// Write a geometric shape.
+    fn write_shape(&mut self, shape: &Shape) {
+        let (ops, fill_op, stroke_op) = match &shape.geometry {
+            Geometry::Rect(size) => (
+                eco_format!("0 0 {} {}", num(size.x), num(size.y)),
+                "rectfill",
+                "rectstroke",
+            ),
+            Geometry::Line(target) => (
+                eco_format!(
+                    "newpath 0 0 moveto {} {} lineto",
+                    num(target.x),
+                    num(target.y),
+                ),
+                "fill",
+                "stroke",
+            ),
+            Geometry::Path(p) => (eco_format!("newpath {}", path(p)), "fill", "stroke"),
+        };
+
+        if let Some(color) = shape.fill.as_ref().and_then(paint_color) {
+            if !matches!(shape.geometry, Geometry::Line(_)) {
+                write_color(&mut self.buf, color);
+                let _ = writeln!(self.buf, "{ops} {fill_op}");
+            }
+        }
+
+        if let Some(stroke) = &shape.stroke {
+            let Some(color) = paint_color(&stroke.paint) else { return };
+            if stroke.thickness <= Abs::zero() {
+                return;
+            }
+            write_color(&mut self.buf, color);
+            write_stroke(&mut self.buf, stroke);
+            let _ = writeln!(self.buf, "{ops} {stroke_op}");
+        }
+    }
+
+    /// Write a raster image. Pixels with an alpha channel are composited over
+    /// white.
+    fn write_image(&mut self, image: &Image, size: Size) {
+        let ImageKind::Raster(raster) = image.kind() else { return };
+        let rgba = raster.dynamic().to_rgba8();
+        let (w, h) = rgba.dimensions();
+        let over_white = |c: u8, a: u8| {
+            let (c, a) = (u16::from(c), u16::from(a));
+            ((c * a + 255 * (255 - a) + 127) / 255) as u8
+        };
+        let rgb: Vec<u8> = rgba
+            .pixels()
+            .flat_map(|&image::Rgba([r, g, b, a])| {
+                [over_white(r, a), over_white(g, a), over_white(b, a)]
+            })
+            .collect();
+
+        let _ = writeln!(self.buf, "{} {} scale", num(size.x), num(size.y));
+        self.buf.push_str("/DeviceRGB setcolorspace\n");
+        let _ = writeln!(
+            self.buf,
+            "<< /ImageType 1 /Width {w} /Height {h} /BitsPerComponent 8 \
+             /Decode [0 1 0 1 0 1] /ImageMatrix [{w} 0 0 {h} 0 0] \
+             /DataSource currentfile /ASCIIHexDecode filter >> image",
+        );
+        write_hex(&mut self.buf, &rgb);
+        self.buf.push_str(">\n");
+    }
+}
+
+/// Whether the font can be embedded as a Type 42 font.
+///
+/// This requires TrueType outlines and a standalone font file since the
+/// `sfnts` array cannot reference a font within a collection.
+fn is_type42(font: &Font) -> bool {
+    font.ttf().tables().glyf.is_some() && !font.data().starts_with(b"ttcf")
+}
+
+/// Write a Type 42 font resource with the font subsetted to the given glyphs.
+///
+/// The subsetter preserves glyph IDs, so the `CharStrings` can refer to the
+/// original glyph IDs.
+fn write_type42(buf: &mut String, index: usize, font: &Font, glyphs: &BTreeSet<u16>) {
+    let ttf = font.ttf();
+    let ids: Vec<u16> = glyphs.iter().copied().collect();
+    let data = typst_pdf::subset_font_data(font, &ids);
+    let units = font.units_per_em();
+    let bbox = ttf.global_bounding_box();
+
+    let _ = writeln!(buf, "%%BeginResource: font F{index}");
+    buf.push_str("12 dict begin\n");
+    let _ = writeln!(buf, "/FontName /F{index} def");
+    buf.push_str("/FontType 42 def\n");
+    buf.push_str("/PaintType 0 def\n");
+    buf.push_str("/FontMatrix [1 0 0 1 0 0] def\n");
+    let _ = writeln!(
+        buf,
+        "/FontBBox [{} {} {} {}] def",
+        (bbox.x_min as f64 / units) as f32,
+        (bbox.y_min as f64 / units) as f32,
+        (bbox.x_max as f64 / units) as f32,
+        (bbox.y_max as f64 / units) as f32,
+    );
+    buf.push_str("/Encoding 256 array 0 1 255 { 1 index exch /.notdef put } for def\n");
+
+    let _ = writeln!(buf, "/CharStrings {} dict dup begin", glyphs.len() + 1);
+    buf.push_str("/.notdef 0 def\n");
+    for &id in glyphs.iter().filter(|&&id| id != 0) {
+        let _ = writeln!(buf, "/{} {id} def", glyph_name(id));
+    }
+    buf.push_str("end readonly def\n");
+
+    buf.push_str("/sfnts [\n");
+    for chunk in sfnts(&data) {
+        buf.push('<');
+        write_hex(buf, chunk);
+        // The padding byte required by the Type 42 specification.
+        buf.push_str("00>\n");
+    }
+    buf.push_str("] def\n");
+
+    buf.push_str("FontName currentdict end definefont pop\n");
+    buf.push_str("%%EndResource\n");
+}
+
+/// Split font data into strings for the `sfnts` array.
+///
+/// PostScript strings are limited in length, so the font data must be split.
+/// The specification requires splits to happen at table boundaries or, for
+/// the `glyf` table, between glyphs.
+fn sfnts(data: &[u8]) -> Vec<&[u8]> {
+    let mut breaks = split_points(data).unwrap_or_default();
+    breaks.retain(|&b| b > 0 && b < data.len());
+    breaks.sort_unstable();
+    breaks.dedup();
+    breaks.push(data.len());
+
+    let mut chunks = vec![];
+    let mut start = 0;
+    let mut last = 0;
+    for b in breaks {
+        if b - start > MAX_STRING_LEN && last > start {
+            chunks.push(&data[start..last]);
+            start = last;
+        }
+        last = b;
+    }
+    chunks.push(&data[start..]);
+    chunks
+}
+
+/// Find the offsets at which font data may be split: the start of each table
+/// and of each glyph in the `glyf` table.
+fn split_points(data: &[u8]) -> Option<Vec<usize>> {
+    let read_u16 = |at: usize| -> Option<usize> {
+        Some(u16::from_be_bytes(data.get(at..at + 2)?.try_into().ok()?) as usize)
+    };
+    let read_u32 = |at: usize| -> Option<usize> {
+        Some(u32::from_be_bytes(data.get(at..at + 4)?.try_into().ok()?) as usize)
+    };
+
+    let mut points = vec![];
+    let mut tables = HashMap::new();
+    for i in 0..read_u16(4)? {
+        let record = 12 + 16 * i;
+        let offset = read_u32(record + 8)?;
+        tables.insert(data.get(record..record + 4)?, offset);
+        points.push(offset);
+    }
+
+    let table = |tag: &[u8; 4]| tables.get(&tag[..]).copied();
+    let (Some(glyf), Some(loca), Some(head), Some(maxp)) =
+        (table(b"glyf"), table(b"loca"), table(b"head"), table(b"maxp"))
+    else {
+        return Some(points);
+    };
+
+    let long = read_u16(head + 50)? != 0;
+    for i in 0..=read_u16(maxp + 4)? {
+        let offset =
+            if long { read_u32(loca + 4 * i)? } else { 2 * read_u16(loca + 2 * i)? };
+        points.push(glyf + offset);
+    }
+
+    Some(points)
+}
+
+/// Build a PostScript path from a Typst path.
+fn path(path: &Path) -> EcoString {
+    let mut buf = EcoString::new();
+    for item in &path.0 {
+        match item {
+            PathItem::MoveTo(p) => {
+                let _ = write!(buf, "{} {} moveto ", num(p.x), num(p.y));
+            }
+            PathItem::LineTo(p) => {
+                let _ = write!(buf, "{} {} lineto ", num(p.x), num(p.y));
+            }
+            PathItem::CubicTo(a, b, p) => {
+                let _ = write!(
+                    buf,
+                    "{} {} {} {} {} {} curveto ",
+                    num(a.x),
+                    num(a.y),
+                    num(b.x),
+                    num(b.y),
+                    num(p.x),
+                    num(p.y),
+                );
+            }
+            PathItem::ClosePath => buf.push_str("closepath "),
+        }
+    }
+    buf.pop();
+    buf
+}
+
+/// Write the operators setting up a stroke, except for its color.
+fn write_stroke(buf: &mut String, stroke: &FixedStroke) {
+    let cap = match stroke.cap {
+        LineCap::Butt => 0,
+        LineCap::Round => 1,
+        LineCap::Square => 2,
+    };
+    let join = match stroke.join {
+        LineJoin::Miter => 0,
+        LineJoin::Round => 1,
+        LineJoin::Bevel => 2,
+    };
+    let _ = writeln!(
+        buf,
+        "{} setlinewidth {cap} setlinecap {join} setlinejoin {} setmiterlimit",
+        num(stroke.thickness),
+        stroke.miter_limit.get() as f32,
+    );
+    if let Some(dash) = &stroke.dash {
+        buf.push('[');
+        for (i, &length) in dash.array.iter().enumerate() {
+            if i > 0 {
+                buf.push(' ');
+            }
+            buf.push_str(&num(length));
+        }
+        let _ = writeln!(buf, "] {} setdash", num(dash.phase));
+    }
+}
+
+/// Write the operator setting the current color.
+fn write_color(buf: &mut String, color: Color) {
+    let [r, g, b, _] = color.to_rgb().to_vec4();
+    let _ = writeln!(buf, "{r} {g} {b} setrgbcolor");
+}
+
+/// The solid color to paint with.
+///
+/// Gradients and patterns are rejected before export.
+fn paint_color(paint: &Paint) -> Option<Color> {
+    match paint {
+        Paint::Solid(color) => Some(*color),
+        Paint::Gradient(_) | Paint::Pattern(_) => None,
+    }
+}
+
+/// Write bytes as hexadecimal digits, broken into lines.
+fn write_hex(buf: &mut String, data: &[u8]) {
+    for line in data.chunks(HEX_LINE_LEN) {
+        for byte in line {
+            let _ = write!(buf, "{byte:02X}");
+        }
+        buf.push('\n');
+    }
+}
+
+/// The name under which a glyph is registered in a Type 42 font.
+fn glyph_name(id: u16) -> EcoString {
+    if id == 0 {
+        ".notdef".into()
+    } else {
+        eco_format!("g{id}")
+    }
+}
+
+/// Format a length as a PostScript number in points.
+fn num(abs: Abs) -> EcoString {
+    eco_format!("{}", abs.to_pt() as f32)
+}
+
+/// Escape text for use in a DSC comment string.
+fn string(text: &str) -> EcoString {
+    let mut buf = EcoString::from("(");
+    for c in text.chars() {
+        match c {
+            '(' | ')' | '\\' => {
+                buf.push('\\');
+                buf.push(c);
+            }
+            ' '..='~' => buf.push(c),
+            _ => buf.push('?'),
+        }
+    }
+    buf.push(')');
+    buf
+}
+
+/// Writes glyph outlines as PostScript path operators in font units.
+struct OutlineWriter {
+    buf: String,
+    /// The last point. Needed to convert quadratic curves, which PostScript
+    /// doesn't support, into cubic ones.
+    last: (f32, f32),
+}
+
+impl OutlineBuilder for OutlineWriter {
+    fn move_to(&mut self, x: f32, y: f32) {
+        let _ = writeln!(self.buf, "{x} {y} moveto");
+        self.last = (x, y);
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        let _ = writeln!(self.buf, "{x} {y} lineto");
+        self.last = (x, y);
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        let (x0, y0) = self.last;
+        self.curve_to(
+            x0 + 2.0 / 3.0 * (x1 - x0),
+            y0 + 2.0 / 3.0 * (y1 - y0),
+            x + 2.0 / 3.0 * (x1 - x),
+            y + 2.0 / 3.0 * (y1 - y),
+            x,
+            y,
+        );
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        let _ = writeln!(self.buf, "{x1} {y1} {x2} {y2} {x} {y} curveto");
+        self.last = (x, y);
+    }
+
+    fn close(&mut self) {
+        self.buf.push_str("closepath\n");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use std::sync::Arc;
+
+    use typst::diag::SourceResult;
+    use typst::foundations::{Bytes, Smart};
+    use typst::layout::{Abs, Angle, Em, Frame, FrameItem, Page, Point, Ratio, Size};
+    use typst::model::Document;
+    use typst::syntax::Span;
+    use typst::text::{Font, Glyph, Lang, TextItem};
+    use typst::visualize::{
+        Color, ColorSpace, FixedStroke, Geometry, Gradient, Image, ImageFormat,
+        LinearGradient, Paint, RasterFormat, VectorFormat,
+    };
+
+    use super::MAX_STRING_LEN;
+
+    fn pt(v: f64) -> Abs {
+        Abs::pt(v)
+    }
+
+    fn text(font: Font, text: &str) -> TextItem {
+        let glyphs = text
+            .char_indices()
+            .map(|(i, c)| {
+                let id = font.ttf().glyph_index(c).unwrap().0;
+                Glyph {
+                    id,
+                    x_advance: font.advance(id).unwrap(),
+                    x_offset: Em::zero(),
+                    range: i as u16..i as u16 + 1,
+                    span: (Span::detached(), 0),
+                }
+            })
+            .collect();
+        TextItem {
+            font,
+            size: pt(10.0),
+            fill: Paint::Solid(Color::BLACK),
+            stroke: None,
+            lang: Lang::ENGLISH,
+            text: text.into(),
+            glyphs,
+        }
+    }
+
+    fn document(frame: Frame) -> Document {
+        let page = Page { frame, numbering: None, number: 1 };
+        Document { pages: vec![page], ..Default::default() }
+    }
+
+    fn export(font: Font) -> String {
+        let mut frame = Frame::soft(Size::new(pt(100.0), pt(50.0)));
+        let rect = Geometry::Rect(Size::new(pt(20.0), pt(10.0)));
+        let shape = rect.filled(Paint::Solid(Color::BLACK));
+        let pos = Point::new(pt(5.0), pt(5.0));
+        frame.push(pos, FrameItem::Shape(shape, Span::detached()));
+        frame.push(Point::new(pt(10.0), pt(30.0)), FrameItem::Text(text(font, "Hi")));
+        String::from_utf8(crate::ps(&document(frame)).unwrap()).unwrap()
+    }
+
+    fn export_image(image: Image) -> SourceResult<Vec<u8>> {
+        let mut frame = Frame::soft(Size::new(pt(100.0), pt(50.0)));
+        let size = Size::new(pt(20.0), pt(20.0));
+        frame.push(Point::zero(), FrameItem::Image(image, size, Span::detached()));
+        crate::ps(&document(frame))
+    }
+
+    fn fonts() -> impl Iterator<Item = Font> {
+        typst_assets::fonts().flat_map(|data| Font::iter(Bytes::from_static(data)))
+    }
+
+    #[test]
+    fn test_truetype_font_is_embedded_as_type42() {
+        let font = fonts().find(super::is_type42).unwrap();
+        let ps = export(font);
+
+        assert!(ps.starts_with("%!PS-Adobe-3.0\n"));
+        assert!(ps.contains("%%Pages: 1\n"));
+        assert!(ps.contains("%%BoundingBox: 0 0 100 50\n"));
+        assert_eq!(ps.matches("/FontType 42 def").count(), 1);
+        assert!(ps.contains("/sfnts [\n<"));
+        assert!(ps.contains("/F0 10 selectfont"));
+        assert_eq!(ps.matches(" glyphshow").count(), 2);
+        assert!(ps.contains("0 0 20 10 rectfill"));
+        assert!(ps.ends_with("showpage\n%%Trailer\n%%EOF\n"));
+    }
+
+    #[test]
+    fn test_type42_font_is_subsetted() {
+        let font = fonts().find(super::is_type42).unwrap();
+        let ps = export(font.clone());
+        let (_, sfnts) = ps.split_once("/sfnts [\n").unwrap();
+        let (sfnts, _) = sfnts.split_once("] def").unwrap();
+        let digits = sfnts.chars().filter(char::is_ascii_hexdigit).count();
+        assert!(digits / 2 < font.data().len());
+    }
+
+    #[test]
+    fn test_image_alpha_is_composited_over_white() {
+        let mut png = Cursor::new(vec![]);
+        image::RgbaImage::from_pixel(1, 1, image::Rgba([0, 0, 255, 0]))
+            .write_to(&mut png, image::ImageFormat::Png)
+            .unwrap();
+        let format = ImageFormat::Raster(RasterFormat::Png);
+        let image = Image::new(Bytes::from(png.into_inner()), format, None).unwrap();
+        let ps = String::from_utf8(export_image(image).unwrap()).unwrap();
+        assert!(ps.contains(">> image\nFFFFFF\n>\n"));
+    }
+
+    #[test]
+    fn test_svg_image_is_rejected() {
+        let svg = b"<svg xmlns='http://www.w3.org/2000/svg' width='1' height='1'/>";
+        let format = ImageFormat::Vector(VectorFormat::Svg);
+        let image = Image::new(Bytes::from_static(svg), format, None).unwrap();
+        let errors = export_image(image).unwrap_err();
+        assert_eq!(errors[0].message, "PostScript export does not support SVG images");
+    }
+
+    #[test]
+    fn test_gradient_is_rejected() {
+        let gradient = Gradient::Linear(Arc::new(LinearGradient {
+            stops: vec![(Color::BLACK, Ratio::zero()), (Color::WHITE, Ratio::one())],
+            angle: Angle::zero(),
+            space: ColorSpace::Oklab,
+            relative: Smart::Auto,
+            anti_alias: true,
+        }));
+        let rect = Geometry::Rect(Size::new(pt(20.0), pt(10.0)));
+        let mut frame = Frame::soft(Size::new(pt(100.0), pt(50.0)));
+        let shape = rect.filled(Paint::Gradient(gradient));
+        frame.push(Point::zero(), FrameItem::Shape(shape, Span::detached()));
+        let errors = crate::ps(&document(frame)).unwrap_err();
+        assert_eq!(errors[0].message, "PostScript export does not support gradients");
+    }
+
+    #[test]
+    fn test_text_stroke_is_rejected() {
+        let font = fonts().find(super::is_type42).unwrap();
+        let mut text = text(font, "Hi");
+        text.stroke = Some(FixedStroke::default());
+        let mut frame = Frame::soft(Size::new(pt(100.0), pt(50.0)));
+        frame.push(Point::new(pt(10.0), pt(30.0)), FrameItem::Text(text));
+        let errors = crate::ps(&document(frame)).unwrap_err();
+        assert_eq!(errors[0].message, "PostScript export does not support text strokes");
+    }
+
+    #[test]
+    fn test_sfnts_respect_string_limit() {
+        let font = fonts()
+            .filter(super::is_type42)
+            .find(|font| font.data().len() > MAX_STRING_LEN)
+            .unwrap();
+        let chunks = super::sfnts(font.data());
+        assert!(chunks.len() > 1);
+        assert!(chunks.iter().all(|chunk| chunk.len() <= MAX_STRING_LEN));
+        let total: usize = chunks.iter().map(|chunk| chunk.len()).sum();
+        assert_eq!(total, font.data().len());
+    }
+
+    #[test]
+    fn test_other_fonts_are_drawn_as_outlines() {
+        let font = fonts().find(|font| !super::is_type42(font)).unwrap();
+        let ps = export(font);
+
+        assert!(!ps.contains("/FontType 42"));
+        assert!(!ps.contains("glyphshow"));
+        assert_eq!(ps.matches("fill grestore").count(), 2);
+    }
+}
@@ -165,7 +165,7 @@ fn render_frame(canvas: &mut sk::Pixmap, state: State, frame: &Frame) {
                 render_image(canvas, state.pre_translate(*pos), image, *size);
             }
             FrameItem::Meta(meta, _) => match meta {
-                Meta::Link(_) => {}
+                Meta::Link(..) => {}
                 Meta::Elem(_) => {}
                 Meta::Hide => {}
             },
@@ -15,6 +15,8 @@ pub struct Tracer {
     warnings_set: HashSet<u128>,
     delayed: EcoVec<SourceDiagnostic>,
     values: EcoVec<(Value, Option<Styles>)>,
+    budget: Option<usize>,
+    spent: usize,
 }
 
 impl Tracer {
@@ -46,6 +48,27 @@ impl Tracer {
     pub fn values(self) -> EcoVec<(Value, Option<Styles>)> {
         self.values
     }
+
+    /// Limit how many grid cells may be measured or laid out in each layout
+    /// iteration.
+    ///
+    /// Once the budget is exhausted, grids stop laying out further cells and
+    /// an error is reported. This bounds the layout cost when compiling
+    /// untrusted documents.
+    pub fn set_budget(&mut self, budget: Option<usize>) {
+        self.budget = budget;
+    }
+
+    /// How many more grid cells may be measured or laid out in the current
+    /// layout iteration, if limited.
+    pub fn remaining_budget(&self) -> Option<usize> {
+        self.budget.map(|budget| budget.saturating_sub(self.spent))
+    }
+
+    /// Restore the full budget for a new layout iteration.
+    pub(crate) fn reset_budget(&mut self) {
+        self.spent = 0;
+    }
 }
 
 #[comemo::track]
@@ -73,6 +96,16 @@ impl Tracer {
         }
     }
 
+    /// Whether the given number of grid cell layouts fits into the budget.
+    pub fn within_budget(&self, cost: usize) -> bool {
+        self.budget.map_or(true, |budget| self.spent + cost <= budget)
+    }
+
+    /// Spend grid cell layouts from the budget.
+    pub fn spend(&mut self, cost: usize) {
+        self.spent += cost;
+    }
+
     /// Trace a value for the span.
     pub fn value(&mut self, v: Value, s: Option<Styles>) {
         if self.values.len() < Self::MAX_VALUES {
@@ -471,7 +471,8 @@ impl Content {
 
     /// Link the content somewhere.
     pub fn linked(self, dest: Destination) -> Self {
-        self.styled(MetaElem::set_data(smallvec![Meta::Link(dest)]))
+        let span = self.span();
+        self.styled(MetaElem::set_data(smallvec![Meta::Link(dest, span)]))
     }
 
     /// Make the content linkable by `.linked(Destination::Location(loc))`.
@@ -33,6 +33,7 @@ use crate::foundations::{
 };
 use crate::model::Destination;
 use crate::realize::{Behave, Behaviour};
+use crate::syntax::Span;
 
 /// Interactions between document parts.
 ///
@@ -90,7 +91,11 @@ impl Behave for Packed<MetaElem> {
 #[derive(Clone, PartialEq, Hash)]
 pub enum Meta {
     /// An internal or external link to a destination.
-    Link(Destination),
+    ///
+    /// The span identifies the linked content, so that the pieces of a link
+    /// that is broken across lines can be told apart from other links to the
+    /// same destination.
+    Link(Destination, Span),
     /// An identifiable element that produces something within the area this
     /// metadata is attached to.
     Elem(Content),
@@ -103,7 +108,7 @@ pub enum Meta {
 impl Debug for Meta {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         match self {
-            Self::Link(dest) => write!(f, "Link({dest:?})"),
+            Self::Link(dest, _) => write!(f, "Link({dest:?})"),
             Self::Elem(content) => write!(f, "Elem({:?})", content.func()),
             Self::Hide => f.pad("Hide"),
         }
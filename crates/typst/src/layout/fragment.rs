@@ -1,6 +1,6 @@
 use std::fmt::{self, Debug, Formatter};
 
-use crate::layout::Frame;
+use crate::layout::{Abs, Frame, Size};
 
 /// A partial layout result.
 #[derive(Clone)]
@@ -27,6 +27,17 @@ impl Fragment {
         self.0.len()
     }
 
+    /// The total extent of the fragment across all of its regions.
+    ///
+    /// This is the width of the widest frame and the sum of the frames'
+    /// heights, as if they were stacked on top of each other.
+    pub fn total_size(&self) -> Size {
+        Size::new(
+            self.0.iter().map(Frame::width).max().unwrap_or_default(),
+            self.0.iter().map(Frame::height).sum::<Abs>(),
+        )
+    }
+
     /// Extract the first and only frame.
     ///
     /// Panics if there are multiple frames.
@@ -87,3 +98,17 @@ impl<'a> IntoIterator for &'a mut Fragment {
         self.0.iter_mut()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_total_size_sums_region_heights() {
+        let frames = [(100.0, 30.0), (100.0, 40.0), (80.0, 20.0)]
+            .map(|(w, h)| Frame::soft(Size::new(Abs::pt(w), Abs::pt(h))));
+        let fragment = Fragment::frames(frames.to_vec());
+        assert_eq!(fragment.total_size(), Size::new(Abs::pt(100.0), Abs::pt(90.0)));
+        assert_eq!(Fragment::frames(vec![]).total_size(), Size::zero());
+    }
+}
@@ -135,6 +135,11 @@ impl Frame {
     pub fn items(&self) -> std::slice::Iter<'_, (Point, FrameItem)> {
         self.items.iter()
     }
+
+    /// A mutable iterator over the items inside this frame.
+    pub(crate) fn items_mut(&mut self) -> std::slice::IterMut<'_, (Point, FrameItem)> {
+        Arc::make_mut(&mut self.items).iter_mut()
+    }
 }
 
 /// Insert items and subframes.
@@ -1,30 +1,32 @@
+use std::collections::VecDeque;
 use std::fmt::Debug;
 use std::hash::Hash;
 use std::num::NonZeroUsize;
 use std::sync::Arc;
 
-use ecow::eco_format;
+use ecow::{eco_format, eco_vec};
 
 use super::lines::{
     generate_line_segments, hline_stroke_at_column, vline_stroke_at_row, Line,
     LinePosition, LineSegment,
 };
 use crate::diag::{
-    bail, At, Hint, HintedStrResult, HintedString, SourceResult, StrResult,
+    bail, error, warning, At, Hint, HintedStrResult, HintedString, SourceResult,
+    StrResult,
 };
 use crate::engine::Engine;
 use crate::foundations::{
-    Array, CastInfo, Content, Context, Fold, FromValue, Func, IntoValue, Reflect,
-    Resolve, Smart, StyleChain, Value,
+    array, cast, Array, Cast, CastInfo, Content, Context, Fold, FromValue, Func,
+    IntoValue, Reflect, Resolve, Smart, StyleChain, Value,
 };
 use crate::layout::{
     Abs, Alignment, Axes, Dir, Fr, Fragment, Frame, FrameItem, LayoutMultiple, Length,
-    Point, Regions, Rel, Sides, Size, Sizing,
+    Point, Ratio, Regions, Rel, Sides, Size, Sizing, Transform,
 };
 use crate::syntax::Span;
 use crate::text::TextElem;
 use crate::util::{MaybeReverseIter, NonZeroExt, Numeric};
-use crate::visualize::{Geometry, Paint, Stroke};
+use crate::visualize::{Geometry, Paint, Path, Stroke};
 
 /// A value that can be configured per cell.
 #[derive(Debug, Clone, PartialEq, Hash)]
@@ -61,6 +63,30 @@ impl<T: Default + Clone + FromValue> Celled<T> {
     }
 }
 
+impl<T: Default + Clone + FromValue> Celled<T> {
+    /// Resolve the value for a whole row. Functions are only passed the row
+    /// index and arrays correspond to rows instead of columns.
+    pub fn resolve_row(
+        &self,
+        engine: &mut Engine,
+        styles: StyleChain,
+        y: usize,
+    ) -> SourceResult<T> {
+        Ok(match self {
+            Self::Value(value) => value.clone(),
+            Self::Func(func) => func
+                .call(engine, &Context::new(None, Some(styles)), [y])?
+                .cast()
+                .at(func.span())?,
+            Self::Array(array) => y
+                .checked_rem(array.len())
+                .and_then(|i| array.get(i))
+                .cloned()
+                .unwrap_or_default(),
+        })
+    }
+}
+
 impl<T: Default> Default for Celled<T> {
     fn default() -> Self {
         Self::Value(T::default())
@@ -185,12 +211,16 @@ pub struct Cell {
     /// override their own stroke properties (and thus have less priority when
     /// defining with which stroke to draw grid lines around this cell).
     pub stroke_overridden: Sides<bool>,
+    /// Whether the cell has no content. Gutter next to empty cells may be
+    /// collapsed.
+    pub empty: bool,
 }
 
 impl From<Content> for Cell {
     /// Create a simple cell given its body.
     fn from(body: Content) -> Self {
         Self {
+            empty: body.is_empty(),
             body,
             fill: None,
             colspan: NonZeroUsize::ONE,
@@ -211,6 +241,46 @@ impl LayoutMultiple for Cell {
     }
 }
 
+/// How to handle cell content that is too wide for its cell.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash, Cast)]
+pub enum CellOverflow {
+    /// The content is clipped at the cell's edges.
+    Clip,
+    /// The content is scaled down until it fits into the cell.
+    Shrink,
+    /// The content is cut into pieces as wide as the cell, which are stacked
+    /// as additional lines below each other.
+    Break,
+    /// The content bleeds past the cell's edges.
+    #[default]
+    Overflow,
+}
+
+/// A range of consecutive content rows which are kept in the same region.
+///
+/// Written as an array of the first row and the row after the last one,
+/// e.g. `{(2, 5)}` for the rows 2, 3 and 4.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct RowGroup {
+    /// The first row in the group.
+    pub start: usize,
+    /// The row after the last row in the group.
+    pub end: usize,
+}
+
+cast! {
+    RowGroup,
+    self => array![self.start, self.end].into_value(),
+    array: Array => {
+        let mut iter = array.into_iter();
+        let (start, end) = match (iter.next(), iter.next(), iter.next()) {
+            (Some(a), Some(b), None) => (a.cast()?, b.cast()?),
+            _ => bail!("array must contain exactly two entries"),
+        };
+        Self { start, end }
+    },
+}
+
 /// A grid entry.
 #[derive(Clone)]
 pub(super) enum Entry {
@@ -264,6 +334,25 @@ pub enum GridItem<T: ResolvableCell> {
     },
     /// A cell in the grid.
     Cell(T),
+    /// A header, whose cells are placed in the first rows of the grid.
+    Header {
+        /// Whether the header should be repeated at the top of each region.
+        repeat: bool,
+        /// The span of the corresponding header element.
+        span: Span,
+        /// The cells in the header.
+        cells: Vec<T>,
+    },
+    /// A footer, whose cells are placed in new rows at the bottom of the
+    /// grid, after all other cells.
+    Footer {
+        /// Whether the footer should be repeated at the bottom of each region.
+        repeat: bool,
+        /// The span of the corresponding footer element.
+        span: Span,
+        /// The cells in the footer.
+        cells: Vec<T>,
+    },
 }
 
 /// Used for cell-like elements which are aware of their final properties in
@@ -284,8 +373,9 @@ pub trait ResolvableCell {
         styles: StyleChain,
     ) -> Cell;
 
-    /// Returns this cell's column override.
-    fn x(&self, styles: StyleChain) -> Smart<usize>;
+    /// Returns this cell's column override. Negative columns are counted
+    /// from the end.
+    fn x(&self, styles: StyleChain) -> Smart<isize>;
 
     /// Returns this cell's row override.
     fn y(&self, styles: StyleChain) -> Smart<usize>;
@@ -297,6 +387,31 @@ pub trait ResolvableCell {
     fn span(&self) -> Span;
 }
 
+/// A header at the top of the grid.
+#[derive(Debug, Copy, Clone)]
+pub struct Header {
+    /// The first row track after the header.
+    ///
+    /// With gutter, the gutter row right below the header's last content row
+    /// (if any) belongs to the header, so that it is repeated together with
+    /// the header.
+    pub end: usize,
+    /// Whether the header is repeated at the top of each region.
+    pub repeated: bool,
+}
+
+/// A footer at the bottom of the grid.
+#[derive(Debug, Copy, Clone)]
+pub struct Footer {
+    /// The first row track belonging to the footer.
+    ///
+    /// With gutter, this is the gutter row right above the footer's first
+    /// content row (if any), so that it is repeated together with the footer.
+    pub start: usize,
+    /// Whether the footer is repeated at the bottom of each region.
+    pub repeated: bool,
+}
+
 /// A grid of cells, including the columns, rows, and cell data.
 pub struct CellGrid {
     /// The grid cells.
@@ -315,6 +430,26 @@ pub struct CellGrid {
     pub(super) hlines: Vec<Vec<Line>>,
     /// Whether this grid has gutters.
     pub(super) has_gutter: bool,
+    /// The header of this grid, if any.
+    pub(super) header: Option<Header>,
+    /// The footer of this grid, if any.
+    pub(super) footer: Option<Footer>,
+    /// The bands filling each content row across its whole width, including
+    /// column gutters.
+    pub(super) row_fills: Vec<Option<Paint>>,
+    /// The width below which each column track never shrinks, if any.
+    pub(super) mins: Vec<Option<Rel<Length>>>,
+    /// Whether each column track keeps its measured width when auto columns
+    /// are shrunk.
+    pub(super) pinned: Vec<bool>,
+    /// Whether each column track is set to the same width as the other
+    /// equalized auto columns.
+    pub(super) equal: Vec<bool>,
+    /// How to handle cell content that is too wide for its cell.
+    pub(super) overflow: CellOverflow,
+    /// Ranges of row tracks which move to the next region together if they
+    /// don't fit into the current one. Trailing gutter is not included.
+    pub(super) row_groups: Vec<std::ops::Range<usize>>,
 }
 
 impl CellGrid {
@@ -340,6 +475,7 @@ impl CellGrid {
         gutter: Axes<&[Sizing]>,
         items: I,
         fill: &Celled<Option<Paint>>,
+        row_fill: &Celled<Option<Paint>>,
         align: &Celled<Smart<Alignment>>,
         inset: &Celled<Sides<Option<Rel<Length>>>>,
         stroke: &ResolvedCelled<Sides<Option<Option<Arc<Stroke>>>>>,
@@ -391,81 +527,19 @@ impl CellGrid {
             bail!(span, "too many cells or lines were given")
         };
         let mut resolved_cells: Vec<Option<Entry>> = Vec::with_capacity(item_count);
-        for item in items {
-            let cell = match item {
-                GridItem::HLine { y, start, end, stroke, span, position } => {
-                    let y = y.unwrap_or_else(|| {
-                        // When no 'y' is specified for the hline, we place it
-                        // under the latest automatically positioned cell.
-                        // The current value of the auto index is always the
-                        // index of the latest automatically positioned cell
-                        // placed plus one (that's what we do in
-                        // 'resolve_cell_position'), so we subtract 1 to get
-                        // that cell's index, and place the hline below its
-                        // row. The exception is when the auto_index is 0,
-                        // meaning no automatically positioned cell was placed
-                        // yet. In that case, we place the hline at the top of
-                        // the table.
-                        auto_index
-                            .checked_sub(1)
-                            .map_or(0, |last_auto_index| last_auto_index / c + 1)
-                    });
-                    if end.is_some_and(|end| end.get() < start) {
-                        bail!(span, "line cannot end before it starts");
-                    }
-                    let line = Line { index: y, start, end, stroke, position };
-
-                    // Since the amount of rows is dynamic, delay placing
-                    // hlines until after all cells were placed so we can
-                    // properly verify if they are valid. Note that we can't
-                    // place hlines even if we already know they would be in a
-                    // valid row, since it's possible that we pushed pending
-                    // hlines in the same row as this one in previous
-                    // iterations, and we need to ensure that hlines from
-                    // previous iterations are pushed to the final vector of
-                    // hlines first - the order of hlines must be kept, as this
-                    // matters when determining which one "wins" in case of
-                    // conflict. Pushing the current hline before we push
-                    // pending hlines later would change their order!
-                    pending_hlines.push((span, line));
-                    continue;
-                }
-                GridItem::VLine { x, start, end, stroke, span, position } => {
-                    let x = x.unwrap_or_else(|| {
-                        // When no 'x' is specified for the vline, we place it
-                        // after the latest automatically positioned cell.
-                        // The current value of the auto index is always the
-                        // index of the latest automatically positioned cell
-                        // placed plus one (that's what we do in
-                        // 'resolve_cell_position'), so we subtract 1 to get
-                        // that cell's index, and place the vline after its
-                        // column. The exception is when the auto_index is 0,
-                        // meaning no automatically positioned cell was placed
-                        // yet. In that case, we place the vline to the left of
-                        // the table.
-                        auto_index
-                            .checked_sub(1)
-                            .map_or(0, |last_auto_index| last_auto_index % c + 1)
-                    });
-                    if end.is_some_and(|end| end.get() < start) {
-                        bail!(span, "line cannot end before it starts");
-                    }
-                    let line = Line { index: x, start, end, stroke, position };
 
-                    // For consistency with hlines, we only push vlines to the
-                    // final vector of vlines after processing every cell.
-                    pending_vlines.push((span, line));
-                    continue;
-                }
-                GridItem::Cell(cell) => cell,
-            };
+        // Places a cell in the grid, given its requested position.
+        let mut place_cell = |cell: T,
+                              resolved_cells: &mut Vec<Option<Entry>>,
+                              auto_index: &mut usize|
+         -> SourceResult<()> {
             let cell_span = cell.span();
             // Let's calculate the cell's final position based on its
             // requested position.
             let resolved_index = {
                 let cell_x = cell.x(styles);
                 let cell_y = cell.y(styles);
-                resolve_cell_position(cell_x, cell_y, &resolved_cells, &mut auto_index, c)
+                resolve_cell_position(cell_x, cell_y, resolved_cells, auto_index, c)
                     .at(cell_span)?
             };
             let x = resolved_index % c;
@@ -560,6 +634,152 @@ impl CellGrid {
                 }
                 *slot = Some(Entry::Merged { parent: resolved_index });
             }
+
+            Ok(())
+        };
+
+        let mut header = None;
+        let mut pending_footer = None;
+        for item in items {
+            let cell = match item {
+                GridItem::HLine { y, start, end, stroke, span, position } => {
+                    let y = y.unwrap_or_else(|| {
+                        // When no 'y' is specified for the hline, we place it
+                        // under the latest automatically positioned cell.
+                        // The current value of the auto index is always the
+                        // index of the latest automatically positioned cell
+                        // placed plus one (that's what we do in
+                        // 'resolve_cell_position'), so we subtract 1 to get
+                        // that cell's index, and place the hline below its
+                        // row. The exception is when the auto_index is 0,
+                        // meaning no automatically positioned cell was placed
+                        // yet. In that case, we place the hline at the top of
+                        // the table.
+                        auto_index
+                            .checked_sub(1)
+                            .map_or(0, |last_auto_index| last_auto_index / c + 1)
+                    });
+                    if end.is_some_and(|end| end.get() < start) {
+                        bail!(span, "line cannot end before it starts");
+                    }
+                    let line = Line { index: y, start, end, stroke, position };
+
+                    // Since the amount of rows is dynamic, delay placing
+                    // hlines until after all cells were placed so we can
+                    // properly verify if they are valid. Note that we can't
+                    // place hlines even if we already know they would be in a
+                    // valid row, since it's possible that we pushed pending
+                    // hlines in the same row as this one in previous
+                    // iterations, and we need to ensure that hlines from
+                    // previous iterations are pushed to the final vector of
+                    // hlines first - the order of hlines must be kept, as this
+                    // matters when determining which one "wins" in case of
+                    // conflict. Pushing the current hline before we push
+                    // pending hlines later would change their order!
+                    pending_hlines.push((span, line));
+                    continue;
+                }
+                GridItem::VLine { x, start, end, stroke, span, position } => {
+                    let x = x.unwrap_or_else(|| {
+                        // When no 'x' is specified for the vline, we place it
+                        // after the latest automatically positioned cell.
+                        // The current value of the auto index is always the
+                        // index of the latest automatically positioned cell
+                        // placed plus one (that's what we do in
+                        // 'resolve_cell_position'), so we subtract 1 to get
+                        // that cell's index, and place the vline after its
+                        // column. The exception is when the auto_index is 0,
+                        // meaning no automatically positioned cell was placed
+                        // yet. In that case, we place the vline to the left of
+                        // the table.
+                        auto_index
+                            .checked_sub(1)
+                            .map_or(0, |last_auto_index| last_auto_index % c + 1)
+                    });
+                    if end.is_some_and(|end| end.get() < start) {
+                        bail!(span, "line cannot end before it starts");
+                    }
+                    let line = Line { index: x, start, end, stroke, position };
+
+                    // For consistency with hlines, we only push vlines to the
+                    // final vector of vlines after processing every cell.
+                    pending_vlines.push((span, line));
+                    continue;
+                }
+                GridItem::Header { repeat, span, cells } => {
+                    if header.is_some() {
+                        bail!(span, "cannot have more than one header");
+                    }
+
+                    if resolved_cells.iter().any(Option::is_some) {
+                        bail!(
+                            span,
+                            "header must start at the first row";
+                            hint: "try moving the header before all other cells"
+                        );
+                    }
+
+                    for cell in cells {
+                        if cell.x(styles).is_custom() || cell.y(styles).is_custom() {
+                            bail!(
+                                cell.span(),
+                                "cell in a header cannot have an explicit position";
+                                hint: "remove the cell's `x` and `y` fields"
+                            );
+                        }
+
+                        place_cell(cell, &mut resolved_cells, &mut auto_index)?;
+                    }
+
+                    // The header occupies whole rows, so cells after it start
+                    // in a new row.
+                    let end = resolved_cells.len().div_ceil(c);
+                    resolved_cells.resize(end * c, None);
+                    auto_index = end * c;
+                    if end > 0 {
+                        header = Some(Header { end, repeated: repeat });
+                    }
+                    continue;
+                }
+                GridItem::Footer { repeat, span, cells } => {
+                    if pending_footer.is_some() {
+                        bail!(span, "cannot have more than one footer");
+                    }
+
+                    // Footer cells are only placed after all other cells, as
+                    // they must appear at the bottom of the grid.
+                    pending_footer = Some((repeat, cells));
+                    continue;
+                }
+                GridItem::Cell(cell) => cell,
+            };
+
+            place_cell(cell, &mut resolved_cells, &mut auto_index)?;
+        }
+
+        // Place the footer's cells in new rows below all other rows (including
+        // rows explicitly specified through the grid's tracks).
+        let mut footer = None;
+        if let Some((repeated, cells)) = pending_footer {
+            let start = (resolved_cells.len() / c).max(tracks.y.len());
+            resolved_cells.resize(start * c, None);
+            auto_index = start * c;
+
+            for cell in cells {
+                if cell.x(styles).is_custom() || cell.y(styles).is_custom() {
+                    bail!(
+                        cell.span(),
+                        "cell in a footer cannot have an explicit position";
+                        hint: "remove the cell's `x` and `y` fields"
+                    );
+                }
+
+                place_cell(cell, &mut resolved_cells, &mut auto_index)?;
+            }
+
+            if resolved_cells.len() > start * c {
+                footer = Some(Footer { start, repeated });
+            }
         }
 
         // Replace absent entries by resolved empty cells, and produce a vector
@@ -672,7 +892,35 @@ impl CellGrid {
             vlines[x].push(line);
         }
 
-        Ok(Self::new_internal(tracks, gutter, vlines, hlines, resolved_cells))
+        let mut grid = Self::new_internal(tracks, gutter, vlines, hlines, resolved_cells);
+        grid.header = header.map(|header| Header {
+            // The header's end row must be converted to a row track. With
+            // gutter, the header includes the gutter row below it.
+            end: if grid.has_gutter {
+                (2 * header.end).min(grid.rows.len())
+            } else {
+                header.end
+            },
+            ..header
+        });
+        grid.footer = footer.map(|footer| Footer {
+            // The footer's start row must be converted to a row track. With
+            // gutter, the footer includes the gutter row above it.
+            start: if grid.has_gutter {
+                (2 * footer.start).saturating_sub(1)
+            } else {
+                footer.start
+            },
+            ..footer
+        });
+
+        let content_rows =
+            if grid.has_gutter { (grid.rows.len() + 1) / 2 } else { grid.rows.len() };
+        grid.row_fills = (0..content_rows)
+            .map(|y| row_fill.resolve_row(engine, styles, y))
+            .collect::<SourceResult<_>>()?;
+
+        Ok(grid)
     }
 
     /// Generates the cell grid, given the tracks and resolved entries.
@@ -727,56 +975,212 @@ impl CellGrid {
             rows.pop();
         }
 
-        Self { cols, rows, entries, vlines, hlines, has_gutter }
+        Self {
+            cols,
+            rows,
+            entries,
+            vlines,
+            hlines,
+            has_gutter,
+            header: None,
+            footer: None,
+            row_fills: vec![],
+            mins: vec![],
+            pinned: vec![],
+            equal: vec![],
+            overflow: CellOverflow::Overflow,
+            row_groups: vec![],
+        }
     }
 
-    /// Get the grid entry in column `x` and row `y`.
+    /// Collapses gutter tracks which only separate empty cells from others.
     ///
-    /// Returns `None` if it's a gutter cell.
-    #[track_caller]
-    pub(super) fn entry(&self, x: usize, y: usize) -> Option<&Entry> {
-        assert!(x < self.cols.len());
-        assert!(y < self.rows.len());
+    /// A gutter column collapses to zero width if, in every row, the cell on
+    /// at least one of its sides is empty. This includes the case where both
+    /// neighbors are empty. Gutter rows collapse analogously.
+    pub fn collapse_gutter(&mut self) {
+        if !self.has_gutter {
+            return;
+        }
 
-        if self.has_gutter {
-            // Even columns and rows are children, odd ones are gutter.
-            if x % 2 == 0 && y % 2 == 0 {
-                let c = 1 + self.cols.len() / 2;
-                self.entries.get((y / 2) * c + x / 2)
-            } else {
-                None
-            }
-        } else {
-            let c = self.cols.len();
-            self.entries.get(y * c + x)
+        let is_empty =
+            |x, y| self.parent_cell(x, y).map_or(true, |cell: &Cell| cell.empty);
+        let collapsed_cols: Vec<usize> = (1..self.cols.len())
+            .step_by(2)
+            .filter(|&x| {
+                (0..self.rows.len())
+                    .step_by(2)
+                    .all(|y| is_empty(x - 1, y) || is_empty(x + 1, y))
+            })
+            .collect();
+        let collapsed_rows: Vec<usize> = (1..self.rows.len())
+            .step_by(2)
+            .filter(|&y| {
+                (0..self.cols.len())
+                    .step_by(2)
+                    .all(|x| is_empty(x, y - 1) || is_empty(x, y + 1))
+            })
+            .collect();
+
+        let zero = Sizing::Rel(Rel::zero());
+        for x in collapsed_cols {
+            self.cols[x] = zero;
+        }
+        for y in collapsed_rows {
+            self.rows[y] = zero;
         }
     }
 
-    /// Get the content of the cell in column `x` and row `y`.
+    /// Gives the content columns lower bounds below which they never shrink.
     ///
-    /// Returns `None` if it's a gutter cell or merged position.
-    #[track_caller]
-    pub(super) fn cell(&self, x: usize, y: usize) -> Option<&Cell> {
-        self.entry(x, y).and_then(Entry::as_cell)
+    /// Like the column sizings, the last bound is repeated for any further
+    /// columns.
+    pub fn set_column_mins(&mut self, mins: &[Option<Rel<Length>>]) {
+        let factor = if self.has_gutter { 2 } else { 1 };
+        self.mins = (0..self.cols.len())
+            .map(|x| {
+                let min = mins.get(x / factor).or(mins.last()).copied().flatten();
+                min.filter(|_| x % factor == 0)
+            })
+            .collect();
     }
 
-    /// Returns the parent cell of the grid entry at the given position.
-    /// - If the entry at the given position is a cell, returns it.
-    /// - If it is a merged cell, returns the parent cell.
-    /// - If it is a gutter cell, returns None.
-    #[track_caller]
-    pub(super) fn parent_cell(&self, x: usize, y: usize) -> Option<&Cell> {
-        self.parent_cell_position(x, y)
-            .and_then(|Axes { x, y }| self.cell(x, y))
+    /// Pins the given content columns, such that they keep their measured
+    /// width when auto columns must shrink to fit.
+    pub fn pin_columns(&mut self, columns: &[usize], span: Span) -> SourceResult<()> {
+        let factor = if self.has_gutter { 2 } else { 1 };
+        let count = self.cols.len().div_ceil(factor);
+        self.pinned.resize(self.cols.len(), false);
+        for &x in columns {
+            if x >= count {
+                bail!(span, "cannot pin column {x} as the grid only has {count} columns");
+            }
+            self.pinned[x * factor] = true;
+        }
+        Ok(())
     }
 
-    /// Returns the position of the parent cell of the grid entry at the given
-    /// position. It is guaranteed to have a non-gutter, non-merged cell at
-    /// the returned position, due to how the grid is built.
-    /// - If the entry at the given position is a cell, returns the given
-    /// position.
-    /// - If it is a merged cell, returns the parent cell's position.
-    /// - If it is a gutter cell, returns None.
+    /// Marks the given content columns, such that they all end up with the
+    /// same width if they are auto columns.
+    pub fn equalize_columns(
+        &mut self,
+        columns: &[usize],
+        span: Span,
+    ) -> SourceResult<()> {
+        let factor = if self.has_gutter { 2 } else { 1 };
+        let count = self.cols.len().div_ceil(factor);
+        self.equal.resize(self.cols.len(), false);
+        for &x in columns {
+            if x >= count {
+                bail!(
+                    span,
+                    "cannot equalize column {x} as the grid only has {count} columns"
+                );
+            }
+            self.equal[x * factor] = true;
+        }
+        Ok(())
+    }
+
+    /// Sets how to handle cell content that is too wide for its cell.
+    pub fn set_overflow(&mut self, overflow: CellOverflow) {
+        self.overflow = overflow;
+    }
+
+    /// Groups the given ranges of content rows, such that each range is
+    /// kept in one region if possible.
+    pub fn group_rows(&mut self, groups: &[RowGroup], span: Span) -> SourceResult<()> {
+        let factor = if self.has_gutter { 2 } else { 1 };
+        let count = self.rows.len().div_ceil(factor);
+        for &RowGroup { start, end } in groups {
+            if start >= end {
+                bail!(span, "row group must contain at least one row");
+            } else if end > count {
+                bail!(
+                    span,
+                    "row group ends at row {end}, but the grid only has {count} rows"
+                );
+            }
+            self.row_groups.push(start * factor..end * factor - (factor - 1));
+        }
+        Ok(())
+    }
+
+    /// Whether the column track `x` is pinned.
+    pub(super) fn is_pinned(&self, x: usize) -> bool {
+        self.pinned.get(x).copied().unwrap_or(false)
+    }
+
+    /// Whether the column track `x` is equalized.
+    pub(super) fn is_equal(&self, x: usize) -> bool {
+        self.equal.get(x).copied().unwrap_or(false)
+    }
+
+    /// The band filling the row track `y`, if any.
+    ///
+    /// A gutter row is only covered if the rows above and below it have the
+    /// same band, such that adjacent rows with equal bands merge into one.
+    pub(super) fn row_fill(&self, y: usize) -> Option<&Paint> {
+        if !self.has_gutter {
+            return self.row_fills.get(y)?.as_ref();
+        }
+
+        let above = self.row_fills.get(y / 2)?.as_ref()?;
+        if y % 2 == 0 {
+            return Some(above);
+        }
+
+        let below = self.row_fills.get(y / 2 + 1)?.as_ref()?;
+        (above == below).then_some(above)
+    }
+
+    /// Get the grid entry in column `x` and row `y`.
+    ///
+    /// Returns `None` if it's a gutter cell.
+    #[track_caller]
+    pub(super) fn entry(&self, x: usize, y: usize) -> Option<&Entry> {
+        assert!(x < self.cols.len());
+        assert!(y < self.rows.len());
+
+        if self.has_gutter {
+            // Even columns and rows are children, odd ones are gutter.
+            if x % 2 == 0 && y % 2 == 0 {
+                let c = 1 + self.cols.len() / 2;
+                self.entries.get((y / 2) * c + x / 2)
+            } else {
+                None
+            }
+        } else {
+            let c = self.cols.len();
+            self.entries.get(y * c + x)
+        }
+    }
+
+    /// Get the content of the cell in column `x` and row `y`.
+    ///
+    /// Returns `None` if it's a gutter cell or merged position.
+    #[track_caller]
+    pub(super) fn cell(&self, x: usize, y: usize) -> Option<&Cell> {
+        self.entry(x, y).and_then(Entry::as_cell)
+    }
+
+    /// Returns the parent cell of the grid entry at the given position.
+    /// - If the entry at the given position is a cell, returns it.
+    /// - If it is a merged cell, returns the parent cell.
+    /// - If it is a gutter cell, returns None.
+    #[track_caller]
+    pub(super) fn parent_cell(&self, x: usize, y: usize) -> Option<&Cell> {
+        self.parent_cell_position(x, y)
+            .and_then(|Axes { x, y }| self.cell(x, y))
+    }
+
+    /// Returns the position of the parent cell of the grid entry at the given
+    /// position. It is guaranteed to have a non-gutter, non-merged cell at
+    /// the returned position, due to how the grid is built.
+    /// - If the entry at the given position is a cell, returns the given
+    /// position.
+    /// - If it is a merged cell, returns the parent cell's position.
+    /// - If it is a gutter cell, returns None.
     #[track_caller]
     pub(super) fn parent_cell_position(&self, x: usize, y: usize) -> Option<Axes<usize>> {
         self.entry(x, y).map(|entry| match entry {
@@ -799,12 +1203,32 @@ impl CellGrid {
 /// `(auto, auto)` cell) and the amount of columns in the grid, returns the
 /// final index of this cell in the vector of resolved cells.
 fn resolve_cell_position(
-    cell_x: Smart<usize>,
+    cell_x: Smart<isize>,
     cell_y: Smart<usize>,
     resolved_cells: &[Option<Entry>],
     auto_index: &mut usize,
     columns: usize,
 ) -> HintedStrResult<usize> {
+    // Resolve a column counted from the end and ensure it exists.
+    let cell_x = match cell_x {
+        Smart::Auto => Smart::Auto,
+        Smart::Custom(x) => {
+            let resolved = if x < 0 {
+                columns.checked_sub(x.unsigned_abs())
+            } else {
+                Some(x.unsigned_abs())
+            };
+            match resolved.filter(|&resolved| resolved < columns) {
+                Some(resolved) => Smart::Custom(resolved),
+                None => {
+                    return Err(HintedString::from(eco_format!(
+                        "cell could not be placed at invalid column {x}"
+                    )))
+                }
+            }
+        }
+    };
+
     // Translates a (x, y) position to the equivalent index in the final cell vector.
     // Errors if the position would be too large.
     let cell_index = |x, y: usize| {
@@ -835,11 +1259,6 @@ fn resolve_cell_position(
         }
         // Cell has chosen at least its column.
         (Smart::Custom(cell_x), cell_y) => {
-            if cell_x >= columns {
-                return Err(HintedString::from(eco_format!(
-                    "cell could not be placed at invalid column {cell_x}"
-                )));
-            }
             if let Smart::Custom(cell_y) = cell_y {
                 // Cell has chosen its exact position.
                 cell_index(cell_x, cell_y)
@@ -899,15 +1318,29 @@ pub struct GridLayouter<'a> {
     /// The sum of `rcols`.
     width: Abs,
     /// Resolve row sizes, by region.
-    rrows: Vec<Vec<RowPiece>>,
+    rrows: VecDeque<Vec<RowPiece>>,
     /// Rows in the current region.
     lrows: Vec<Row>,
     /// The initial size of the current region before we started subtracting.
     initial: Size,
     /// Frames for finished regions.
-    finished: Vec<Frame>,
+    finished: VecDeque<Frame>,
+    /// The height reserved for the repeating footer at the bottom of the
+    /// current region. `None` if there is no repeating footer or if it
+    /// doesn't fit into the current region.
+    footer_height: Option<Abs>,
+    /// Whether space for the repeating footer should be reserved in new
+    /// regions.
+    repeat_footer: bool,
+    /// Whether the repeating header should be placed at the top of new
+    /// regions. This is only enabled once the header was laid out the first
+    /// time.
+    repeat_header: bool,
     /// Whether this is an RTL grid.
     is_rtl: bool,
+    /// Whether the engine's budget for cell layouts ran out, after which no
+    /// more cells are laid out.
+    exhausted: bool,
     /// The span of the grid element.
     span: Span,
 }
@@ -921,13 +1354,64 @@ pub struct RowPiece {
     pub y: usize,
 }
 
+/// An iterator over the frames of a grid's regions, created by
+/// [`GridLayouter::layout_regions`].
+pub struct GridRegions<'a, 'e, 'w> {
+    /// The layouter that lays out the rows.
+    layouter: GridLayouter<'a>,
+    /// The engine used for layout.
+    engine: &'e mut Engine<'w>,
+    /// The next step of the layout.
+    stage: Stage,
+}
+
+impl Iterator for GridRegions<'_, '_, '_> {
+    type Item = SourceResult<Frame>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(frame) = self.layouter.take_finished() {
+                return Some(Ok(frame));
+            }
+
+            if matches!(self.stage, Stage::Done) {
+                return None;
+            }
+
+            match self.layouter.step(self.engine, self.stage) {
+                Ok(stage) => self.stage = stage,
+                Err(err) => {
+                    self.stage = Stage::Done;
+                    self.layouter.finished.clear();
+                    self.layouter.rrows.clear();
+                    return Some(Err(err));
+                }
+            }
+        }
+    }
+}
+
+/// How far the layout of a grid has progressed.
+#[derive(Debug, Copy, Clone)]
+enum Stage {
+    /// Nothing was laid out yet.
+    Start,
+    /// The row with the given index is laid out next.
+    Row(usize),
+    /// All rows were laid out, but the last region is not finished yet.
+    Finish,
+    /// The layout is complete.
+    Done,
+}
+
 /// Produced by initial row layout, auto and relative rows are already finished,
 /// fractional rows not yet.
 enum Row {
     /// Finished row frame of auto or relative row with y index.
     Frame(Frame, usize),
-    /// Fractional row with y index.
-    Fr(Fr, usize),
+    /// Fractional row with y index and the height of its tallest cell, below
+    /// which it doesn't shrink.
+    Fr(Fr, usize, Abs),
 }
 
 impl<'a> GridLayouter<'a> {
@@ -951,243 +1435,422 @@ impl<'a> GridLayouter<'a> {
             styles,
             rcols: vec![Abs::zero(); grid.cols.len()],
             width: Abs::zero(),
-            rrows: vec![],
+            rrows: VecDeque::new(),
             lrows: vec![],
             initial: regions.size,
-            finished: vec![],
+            finished: VecDeque::new(),
+            footer_height: None,
+            repeat_footer: false,
+            repeat_header: false,
             is_rtl: TextElem::dir_in(styles) == Dir::RTL,
+            exhausted: false,
             span,
         }
     }
 
     /// Determines the columns sizes and then layouts the grid row-by-row.
-    pub fn layout(mut self, engine: &mut Engine) -> SourceResult<Fragment> {
-        self.measure_columns(engine)?;
+    pub fn layout(self, engine: &mut Engine) -> SourceResult<Fragment> {
+        let frames = self.layout_regions(engine).collect::<SourceResult<_>>()?;
+        Ok(Fragment::frames(frames))
+    }
 
-        for y in 0..self.grid.rows.len() {
-            // Skip to next region if current one is full, but only for content
-            // rows, not for gutter rows.
-            if self.regions.is_full() && (!self.grid.has_gutter || y % 2 == 0) {
-                self.finish_region(engine)?;
+    /// Lays out the grid lazily, yielding the frame of each region as soon as
+    /// it is finished.
+    ///
+    /// Rows are only laid out as far as needed to finish the next region, so
+    /// consumers can process the first regions of a large grid before the
+    /// rest of it was laid out.
+    pub fn layout_regions<'e, 'w>(
+        self,
+        engine: &'e mut Engine<'w>,
+    ) -> GridRegions<'a, 'e, 'w> {
+        GridRegions { layouter: self, engine, stage: Stage::Start }
+    }
+
+    /// Performs the given step of the layout and returns the next one.
+    fn step(&mut self, engine: &mut Engine, stage: Stage) -> SourceResult<Stage> {
+        Ok(match stage {
+            Stage::Start => {
+                self.measure_columns(engine)?;
+
+                // A repeating footer is laid out separately at the bottom of
+                // each region, so its rows are excluded from the regular row
+                // layout.
+                if self.grid.footer.is_some_and(|footer| footer.repeated) {
+                    self.repeat_footer = true;
+                    self.reserve_footer(engine)?;
+                    self.initial = self.regions.size;
+                }
+
+                Stage::Row(0)
             }
+            Stage::Row(y) => {
+                // The header is laid out like regular rows the first time and
+                // only repeated once the rows after it start.
+                let header = self.grid.header.filter(|header| header.repeated);
+                if header.is_some_and(|header| header.end == y) {
+                    self.repeat_header = true;
+                }
+
+                let footer = self.grid.footer.filter(|footer| footer.repeated);
+                if footer.is_some_and(|footer| footer.start == y) {
+                    if self.footer_height.is_some() {
+                        return Ok(Stage::Finish);
+                    }
+
+                    // The footer didn't fit into the last region, so we lay it
+                    // out like regular rows, which may cause it to break.
+                    self.repeat_footer = false;
+                }
+
+                if y >= self.grid.rows.len() {
+                    return Ok(Stage::Finish);
+                }
 
-            match self.grid.rows[y] {
-                Sizing::Auto => self.layout_auto_row(engine, y)?,
-                Sizing::Rel(v) => self.layout_relative_row(engine, v, y)?,
-                Sizing::Fr(v) => self.lrows.push(Row::Fr(v, y)),
+                if self.exhausted {
+                    return Ok(Stage::Finish);
+                }
+
+                self.layout_row(engine, y)?;
+                Stage::Row(y + 1)
             }
+            Stage::Finish => {
+                // There are no more rows, so the header must not be placed
+                // into another region.
+                self.repeat_header = false;
+                self.finish_region(engine)?;
+                Stage::Done
+            }
+            Stage::Done => Stage::Done,
+        })
+    }
+
+    /// Charges a single cell layout to the engine's budget.
+    ///
+    /// Returns `false` and emits an error once the budget is exhausted, in
+    /// which case the caller should skip the cell. No further rows are laid
+    /// out then. The error is delayed so that the regions laid out so far
+    /// stay intact.
+    fn charge(&mut self, engine: &mut Engine) -> bool {
+        if !self.exhausted && engine.tracer.within_budget(1) {
+            engine.tracer.spend(1);
+            return true;
         }
 
-        self.finish_region(engine)?;
+        if !self.exhausted {
+            engine.tracer.delay(eco_vec![error!(
+                self.span,
+                "grid layout exceeded the budget for cell layouts";
+                hint: "try reducing the number of cells"
+            )]);
+            self.exhausted = true;
+        }
 
-        self.render_fills_strokes()
+        false
     }
 
-    /// Add lines and backgrounds.
-    fn render_fills_strokes(mut self) -> SourceResult<Fragment> {
-        let mut finished = std::mem::take(&mut self.finished);
-        for (frame, rows) in finished.iter_mut().zip(&self.rrows) {
-            if self.rcols.is_empty() || rows.is_empty() {
-                continue;
+    /// Returns the cell at the given position if its layout could be charged
+    /// to the engine's budget.
+    fn charged_cell(
+        &mut self,
+        engine: &mut Engine,
+        x: usize,
+        y: usize,
+    ) -> Option<&'a Cell> {
+        let grid = self.grid;
+        grid.cell(x, y).filter(|_| self.charge(engine))
+    }
+
+    /// Takes the first finished region, if any, and adds its lines and
+    /// backgrounds.
+    fn take_finished(&mut self) -> Option<Frame> {
+        let mut frame = self.finished.pop_front()?;
+        let rows = self.rrows.pop_front()?;
+        self.render_fills_strokes(&mut frame, &rows);
+        Some(frame)
+    }
+
+    /// Layout the row with the given index.
+    fn layout_row(&mut self, engine: &mut Engine, y: usize) -> SourceResult<()> {
+        // Skip to next region if current one is full, but only for content
+        // rows, not for gutter rows.
+        if self.regions.is_full() && (!self.grid.has_gutter || y % 2 == 0) {
+            self.finish_region(engine)?;
+        }
+
+        if let Some(group) = self.grid.row_groups.iter().find(|group| group.start == y) {
+            self.place_row_group(engine, group.clone())?;
+        }
+
+        match self.grid.rows[y] {
+            Sizing::Auto => self.layout_auto_row(engine, y)?,
+            Sizing::Rel(v) => self.layout_relative_row(engine, v, y)?,
+            Sizing::Fr(v) => {
+                // The row's content height is reserved right away, so that the
+                // following rows leave enough space for it.
+                let min = self.measure_unbreakable_row(engine, y)?;
+                self.regions.size.y -= min;
+                self.lrows.push(Row::Fr(v, y, min));
             }
+        }
 
-            // Render grid lines.
-            // We collect lines into a vector before rendering so we can sort
-            // them based on thickness, such that the lines with largest
-            // thickness are drawn on top; and also so we can prepend all of
-            // them at once in the frame, as calling prepend() for each line,
-            // and thus pushing all frame items forward each time, would result
-            // in quadratic complexity.
-            let mut lines = vec![];
-
-            // Render vertical lines.
-            // Render them first so horizontal lines have priority later.
-            for (x, dx) in points(self.rcols.iter().copied()).enumerate() {
-                let dx = if self.is_rtl { self.width - dx } else { dx };
-                let is_end_border = x == self.grid.cols.len();
-                let vlines_at_column = self
-                    .grid
-                    .vlines
-                    .get(if !self.grid.has_gutter {
-                        x
-                    } else if is_end_border {
-                        // The end border has its own vector of lines, but
-                        // dividing it by 2 and flooring would give us the
-                        // vector of lines with the index of the last column.
-                        // Add 1 so we get the border's lines.
-                        x / 2 + 1
-                    } else {
-                        // If x is a gutter column, this will round down to the
-                        // index of the previous content column, which is
-                        // intentional - the only lines which can appear before
-                        // a gutter column are lines for the previous column
-                        // marked with "LinePosition::After". Therefore, we get
-                        // the previous column's lines. Worry not, as
-                        // 'generate_line_segments' will correctly filter lines
-                        // based on their LinePosition for us.
-                        //
-                        // If x is a content column, this will correctly return
-                        // its index before applying gutters, so nothing
-                        // special here (lines with "LinePosition::After" would
-                        // then be ignored for this column, as we are drawing
-                        // lines before it, not after).
-                        x / 2
-                    })
-                    .map(|vlines| &**vlines)
-                    .unwrap_or(&[]);
-                let tracks = rows.iter().map(|row| (row.y, row.height));
-
-                // Determine all different line segments we have to draw in
-                // this column, and convert them to points and shapes.
-                //
-                // Even a single, uniform line might generate more than one
-                // segment, if it happens to cross a colspan (over which it
-                // must not be drawn).
-                let segments = generate_line_segments(
-                    self.grid,
-                    tracks,
-                    x,
-                    vlines_at_column,
-                    is_end_border,
-                    vline_stroke_at_row,
+        Ok(())
+    }
+
+    /// Moves to the next region if the given group of row tracks doesn't fit
+    /// into the current one, but would fit into a fresh region.
+    ///
+    /// If even a fresh region is too small, the group breaks normally and a
+    /// warning is emitted.
+    fn place_row_group(
+        &mut self,
+        engine: &mut Engine,
+        group: std::ops::Range<usize>,
+    ) -> SourceResult<()> {
+        let mut height = Abs::zero();
+        for y in group {
+            height += match self.grid.rows[y] {
+                Sizing::Auto => self.measure_unbreakable_row(engine, y)?,
+                Sizing::Rel(v) => {
+                    v.resolve(self.styles).relative_to(self.regions.base().y)
+                }
+                Sizing::Fr(_) => Abs::zero(),
+            };
+        }
+
+        // The height of the region following the current one, without the
+        // space reserved for a repeating footer.
+        let next = self.regions.backlog.first().copied().or(self.regions.last);
+        let fresh =
+            next.unwrap_or(self.regions.full) - self.footer_height.unwrap_or_default();
+
+        if !fresh.fits(height) {
+            engine.tracer.warn(warning!(
+                self.span,
+                "row group does not fit into a single region";
+                hint: "its rows will be broken across regions"
+            ));
+        } else if !self.regions.size.y.fits(height)
+            && !self.lrows.is_empty()
+            && next.is_some()
+        {
+            self.finish_region(engine)?;
+        }
+
+        Ok(())
+    }
+
+    /// Add lines and backgrounds to the frame of a finished region.
+    fn render_fills_strokes(&self, frame: &mut Frame, rows: &[RowPiece]) {
+        if self.rcols.is_empty() || rows.is_empty() {
+            return;
+        }
+
+        // Render grid lines.
+        // We collect lines into a vector before rendering so we can sort
+        // them based on thickness, such that the lines with largest
+        // thickness are drawn on top; and also so we can prepend all of
+        // them at once in the frame, as calling prepend() for each line,
+        // and thus pushing all frame items forward each time, would result
+        // in quadratic complexity.
+        let mut lines = vec![];
+
+        // Render vertical lines.
+        // Render them first so horizontal lines have priority later.
+        for (x, dx) in points(self.rcols.iter().copied()).enumerate() {
+            let dx = if self.is_rtl { self.width - dx } else { dx };
+            let is_end_border = x == self.grid.cols.len();
+            let vlines_at_column = self
+                .grid
+                .vlines
+                .get(if !self.grid.has_gutter {
+                    x
+                } else if is_end_border {
+                    // The end border has its own vector of lines, but
+                    // dividing it by 2 and flooring would give us the
+                    // vector of lines with the index of the last column.
+                    // Add 1 so we get the border's lines.
+                    x / 2 + 1
+                } else {
+                    // If x is a gutter column, this will round down to the
+                    // index of the previous content column, which is
+                    // intentional - the only lines which can appear before
+                    // a gutter column are lines for the previous column
+                    // marked with "LinePosition::After". Therefore, we get
+                    // the previous column's lines. Worry not, as
+                    // 'generate_line_segments' will correctly filter lines
+                    // based on their LinePosition for us.
+                    //
+                    // If x is a content column, this will correctly return
+                    // its index before applying gutters, so nothing
+                    // special here (lines with "LinePosition::After" would
+                    // then be ignored for this column, as we are drawing
+                    // lines before it, not after).
+                    x / 2
+                })
+                .map(|vlines| &**vlines)
+                .unwrap_or(&[]);
+            let tracks = rows.iter().map(|row| (row.y, row.height));
+
+            // Determine all different line segments we have to draw in
+            // this column, and convert them to points and shapes.
+            //
+            // Even a single, uniform line might generate more than one
+            // segment, if it happens to cross a colspan (over which it
+            // must not be drawn).
+            let segments = generate_line_segments(
+                self.grid,
+                tracks,
+                x,
+                vlines_at_column,
+                is_end_border,
+                vline_stroke_at_row,
+            )
+            .map(|segment| {
+                let LineSegment { stroke, offset: dy, length, priority } = segment;
+                let stroke = (*stroke).clone().unwrap_or_default();
+                let thickness = stroke.thickness;
+                let half = thickness / 2.0;
+                let target = Point::with_y(length + thickness);
+                let vline = Geometry::Line(target).stroked(stroke);
+                (
+                    thickness,
+                    priority,
+                    Point::new(dx, dy - half),
+                    FrameItem::Shape(vline, self.span),
                 )
-                .map(|segment| {
-                    let LineSegment { stroke, offset: dy, length, priority } = segment;
-                    let stroke = (*stroke).clone().unwrap_or_default();
-                    let thickness = stroke.thickness;
-                    let half = thickness / 2.0;
-                    let target = Point::with_y(length + thickness);
-                    let vline = Geometry::Line(target).stroked(stroke);
-                    (
-                        thickness,
-                        priority,
-                        Point::new(dx, dy - half),
-                        FrameItem::Shape(vline, self.span),
-                    )
-                });
+            });
 
-                lines.extend(segments);
-            }
+            lines.extend(segments);
+        }
 
-            // Render horizontal lines.
-            // They are rendered second as they default to appearing on top.
-            // First, calculate their offsets from the top of the frame.
-            let hline_offsets = points(rows.iter().map(|piece| piece.height));
-
-            // Additionally, determine their indices (the indices of the
-            // rows they are drawn on top of). In principle, this will
-            // correspond to the rows' indices directly, except for the
-            // first and last hlines, which must be 0 and (amount of rows)
-            // respectively, as they are always drawn (due to being part of
-            // the table's border).
-            let hline_indices = std::iter::once(0)
-                .chain(rows.iter().map(|piece| piece.y).skip(1))
-                .chain(std::iter::once(self.grid.rows.len()));
-
-            for (y, dy) in hline_indices.zip(hline_offsets) {
-                let is_bottom_border = y == self.grid.rows.len();
-                let hlines_at_row = self
-                    .grid
-                    .hlines
-                    .get(if !self.grid.has_gutter {
-                        y
-                    } else if is_bottom_border {
-                        y / 2 + 1
-                    } else {
-                        // Check the vlines loop for an explanation regarding
-                        // these index operations.
-                        y / 2
-                    })
-                    .map(|hlines| &**hlines)
-                    .unwrap_or(&[]);
-                let tracks = self.rcols.iter().copied().enumerate();
-
-                // Determine all different line segments we have to draw in
-                // this row, and convert them to points and shapes.
-                let segments = generate_line_segments(
-                    self.grid,
-                    tracks,
-                    y,
-                    hlines_at_row,
-                    is_bottom_border,
-                    hline_stroke_at_column,
+        // Render horizontal lines.
+        // They are rendered second as they default to appearing on top.
+        // First, calculate their offsets from the top of the frame.
+        let hline_offsets = points(rows.iter().map(|piece| piece.height));
+
+        // Additionally, determine their indices (the indices of the
+        // rows they are drawn on top of). In principle, this will
+        // correspond to the rows' indices directly, except for the
+        // first and last hlines, which must be 0 and (amount of rows)
+        // respectively, as they are always drawn (due to being part of
+        // the table's border).
+        let hline_indices = std::iter::once(0)
+            .chain(rows.iter().map(|piece| piece.y).skip(1))
+            .chain(std::iter::once(self.grid.rows.len()));
+
+        for (y, dy) in hline_indices.zip(hline_offsets) {
+            let is_bottom_border = y == self.grid.rows.len();
+            let hlines_at_row = self
+                .grid
+                .hlines
+                .get(if !self.grid.has_gutter {
+                    y
+                } else if is_bottom_border {
+                    y / 2 + 1
+                } else {
+                    // Check the vlines loop for an explanation regarding
+                    // these index operations.
+                    y / 2
+                })
+                .map(|hlines| &**hlines)
+                .unwrap_or(&[]);
+            let tracks = self.rcols.iter().copied().enumerate();
+
+            // Determine all different line segments we have to draw in
+            // this row, and convert them to points and shapes.
+            let segments = generate_line_segments(
+                self.grid,
+                tracks,
+                y,
+                hlines_at_row,
+                is_bottom_border,
+                hline_stroke_at_column,
+            )
+            .map(|segment| {
+                let LineSegment { stroke, offset: dx, length, priority } = segment;
+                let stroke = (*stroke).clone().unwrap_or_default();
+                let thickness = stroke.thickness;
+                let half = thickness / 2.0;
+                let dx = if self.is_rtl { self.width - dx - length } else { dx };
+                let target = Point::with_x(length + thickness);
+                let hline = Geometry::Line(target).stroked(stroke);
+                (
+                    thickness,
+                    priority,
+                    Point::new(dx - half, dy),
+                    FrameItem::Shape(hline, self.span),
                 )
-                .map(|segment| {
-                    let LineSegment { stroke, offset: dx, length, priority } = segment;
-                    let stroke = (*stroke).clone().unwrap_or_default();
-                    let thickness = stroke.thickness;
-                    let half = thickness / 2.0;
-                    let dx = if self.is_rtl { self.width - dx - length } else { dx };
-                    let target = Point::with_x(length + thickness);
-                    let hline = Geometry::Line(target).stroked(stroke);
-                    (
-                        thickness,
-                        priority,
-                        Point::new(dx - half, dy),
-                        FrameItem::Shape(hline, self.span),
-                    )
-                });
+            });
+
+            // Draw later (after we sort all lines below.)
+            lines.extend(segments);
+        }
 
-                // Draw later (after we sort all lines below.)
-                lines.extend(segments);
+        // Sort by increasing thickness, so that we draw larger strokes
+        // on top. When the thickness is the same, sort by priority.
+        //
+        // Sorting by thickness avoids layering problems where a smaller
+        // hline appears "inside" a larger vline. When both have the same
+        // size, hlines are drawn on top (since the sort is stable, and
+        // they are pushed later).
+        lines.sort_by_key(|(thickness, priority, ..)| (*thickness, *priority));
+
+        // Render cell backgrounds.
+        // We collect them into a vector so they can all be prepended at
+        // once to the frame, together with lines.
+        let mut fills = vec![];
+
+        // Row bands span the whole width of the grid, including column
+        // gutters, and lie below cell fills.
+        let mut dy = Abs::zero();
+        for row in rows {
+            if let Some(fill) = self.grid.row_fill(row.y) {
+                let size = Size::new(self.width, row.height);
+                let rect = Geometry::Rect(size).filled(fill.clone());
+                fills.push((Point::with_y(dy), FrameItem::Shape(rect, self.span)));
             }
+            dy += row.height;
+        }
 
-            // Sort by increasing thickness, so that we draw larger strokes
-            // on top. When the thickness is the same, sort by priority.
-            //
-            // Sorting by thickness avoids layering problems where a smaller
-            // hline appears "inside" a larger vline. When both have the same
-            // size, hlines are drawn on top (since the sort is stable, and
-            // they are pushed later).
-            lines.sort_by_key(|(thickness, priority, ..)| (*thickness, *priority));
-
-            // Render cell backgrounds.
-            // We collect them into a vector so they can all be prepended at
-            // once to the frame, together with lines.
-            let mut fills = vec![];
-
-            // Reverse with RTL so that later columns start first.
-            let mut dx = Abs::zero();
-            for (x, &col) in self.rcols.iter().enumerate().rev_if(self.is_rtl) {
-                let mut dy = Abs::zero();
-                for row in rows {
-                    if let Some(cell) = self.grid.cell(x, row.y) {
-                        let fill = cell.fill.clone();
-                        if let Some(fill) = fill {
-                            let width = self.cell_spanned_width(x, cell.colspan.get());
-                            // In the grid, cell colspans expand to the right,
-                            // so we're at the leftmost (lowest 'x') column
-                            // spanned by the cell. However, in RTL, cells
-                            // expand to the left. Therefore, without the
-                            // offset below, cell fills would start at the
-                            // rightmost visual position of a cell and extend
-                            // over to unrelated columns to the right in RTL.
-                            // We avoid this by ensuring the fill starts at the
-                            // very left of the cell, even with colspan > 1.
-                            let offset =
-                                if self.is_rtl { -width + col } else { Abs::zero() };
-                            let pos = Point::new(dx + offset, dy);
-                            let size = Size::new(width, row.height);
-                            let rect = Geometry::Rect(size).filled(fill);
-                            fills.push((pos, FrameItem::Shape(rect, self.span)));
-                        }
+        // Reverse with RTL so that later columns start first.
+        let mut dx = Abs::zero();
+        for (x, &col) in self.rcols.iter().enumerate().rev_if(self.is_rtl) {
+            let mut dy = Abs::zero();
+            for row in rows {
+                if let Some(cell) = self.grid.cell(x, row.y) {
+                    let fill = cell.fill.clone();
+                    if let Some(fill) = fill {
+                        let width = self.cell_spanned_width(x, cell.colspan.get());
+                        // In the grid, cell colspans expand to the right,
+                        // so we're at the leftmost (lowest 'x') column
+                        // spanned by the cell. However, in RTL, cells
+                        // expand to the left. Therefore, without the
+                        // offset below, cell fills would start at the
+                        // rightmost visual position of a cell and extend
+                        // over to unrelated columns to the right in RTL.
+                        // We avoid this by ensuring the fill starts at the
+                        // very left of the cell, even with colspan > 1.
+                        let offset = if self.is_rtl { -width + col } else { Abs::zero() };
+                        let pos = Point::new(dx + offset, dy);
+                        let size = Size::new(width, row.height);
+                        let rect = Geometry::Rect(size).filled(fill);
+                        fills.push((pos, FrameItem::Shape(rect, self.span)));
                     }
-                    dy += row.height;
                 }
-                dx += col;
+                dy += row.height;
             }
-
-            // Now we render each fill and stroke by prepending to the frame,
-            // such that both appear below cell contents. Fills come first so
-            // that they appear below lines.
-            frame.prepend_multiple(
-                fills
-                    .into_iter()
-                    .chain(lines.into_iter().map(|(_, _, point, shape)| (point, shape))),
-            );
+            dx += col;
         }
 
-        Ok(Fragment::frames(finished))
+        // Now we render each fill and stroke by prepending to the frame,
+        // such that both appear below cell contents. Fills come first so
+        // that they appear below lines.
+        frame.prepend_multiple(
+            fills
+                .into_iter()
+                .chain(lines.into_iter().map(|(_, _, point, shape)| (point, shape))),
+        );
     }
 
     /// Determine all column sizes.
@@ -1198,34 +1861,66 @@ impl<'a> GridLayouter<'a> {
         // Sum of fractions of all fractional tracks.
         let mut fr = Fr::zero();
 
+        // Whether there is at least one auto column.
+        let mut has_auto = false;
+
         // Resolve the size of all relative columns and compute the sum of all
         // fractional tracks.
-        for (&col, rcol) in self.grid.cols.iter().zip(&mut self.rcols) {
-            match col {
-                Sizing::Auto => {}
+        for x in 0..self.grid.cols.len() {
+            let min = self.column_min(x);
+            match self.grid.cols[x] {
+                Sizing::Auto => has_auto = true,
                 Sizing::Rel(v) => {
-                    let resolved =
+                    let mut resolved =
                         v.resolve(self.styles).relative_to(self.regions.base().x);
-                    *rcol = resolved;
+                    if let Some(min) = min {
+                        resolved.set_max(min);
+                    }
+                    self.rcols[x] = resolved;
                     rel += resolved;
                 }
-                Sizing::Fr(v) => fr += v,
+                Sizing::Fr(v) => {
+                    // The lower bound is reserved up front, such that the
+                    // column keeps it even if there isn't enough space.
+                    if let Some(min) = min {
+                        self.rcols[x] = min;
+                        rel += min;
+                    }
+                    fr += v;
+                }
             }
         }
 
         // Size that is not used by fixed-size columns.
         let available = self.regions.size.x - rel;
         if available >= Abs::zero() {
-            // Determine size of auto columns.
-            let (auto, count) = self.measure_auto_columns(engine, available)?;
-
-            // If there is remaining space, distribute it to fractional columns,
-            // otherwise shrink auto columns.
-            let remaining = available - auto;
-            if remaining >= Abs::zero() {
-                self.grow_fractional_columns(remaining, fr);
+            if has_auto {
+                // Determine size of auto columns.
+                let (auto, count) = self.measure_auto_columns(engine, available)?;
+
+                // If there is remaining space, distribute it to fractional
+                // columns, otherwise shrink auto columns.
+                let remaining = available - auto;
+                if remaining >= Abs::zero() {
+                    self.grow_fractional_columns(remaining, fr);
+                } else {
+                    self.shrink_auto_columns(available, count);
+                }
+
+                self.equalize_auto_columns();
             } else {
-                self.shrink_auto_columns(available, count);
+                // Without auto columns, there is nothing to measure, so we
+                // can skip laying out cells and give all space to fractional
+                // columns right away.
+                self.grow_fractional_columns(available, fr);
+            }
+        } else {
+            // Without any space left, auto columns aren't measured, but still
+            // keep their lower bound.
+            for x in 0..self.rcols.len() {
+                if self.grid.cols[x] == Sizing::Auto {
+                    self.rcols[x] = self.column_min(x).unwrap_or_default();
+                }
             }
         }
 
@@ -1235,6 +1930,38 @@ impl<'a> GridLayouter<'a> {
         Ok(())
     }
 
+    /// Set all equalized auto columns to the average of their widths.
+    ///
+    /// The total width of the columns is kept, so no other column is
+    /// affected. Every equalized column gets exactly the same width and any
+    /// rounding remainder is left unused at the end of the grid.
+    fn equalize_auto_columns(&mut self) {
+        let grid = self.grid;
+        let equal = |x: usize| grid.cols[x] == Sizing::Auto && grid.is_equal(x);
+        let count = (0..grid.cols.len()).filter(|&x| equal(x)).count();
+        if count < 2 {
+            return;
+        }
+
+        let total: Abs = (0..grid.cols.len())
+            .filter(|&x| equal(x))
+            .map(|x| self.rcols[x])
+            .sum();
+
+        let width = total / count as f64;
+        for (x, rcol) in self.rcols.iter_mut().enumerate() {
+            if equal(x) {
+                *rcol = width;
+            }
+        }
+    }
+
+    /// The lower bound of the column track `x`, if any.
+    fn column_min(&self, x: usize) -> Option<Abs> {
+        let min = self.grid.mins.get(x).copied().flatten()?;
+        Some(min.resolve(self.styles).relative_to(self.regions.base().x))
+    }
+
     /// Total width spanned by the cell (among resolved columns).
     /// Includes spanned gutter columns.
     fn cell_spanned_width(&self, x: usize, colspan: usize) -> Abs {
@@ -1343,12 +2070,20 @@ impl<'a> GridLayouter<'a> {
                 // cell if it spans all fractional columns in a finite region.
                 let already_covered_width = self.cell_spanned_width(parent_x, colspan);
 
+                if !self.charge(engine) {
+                    continue;
+                }
+
                 let size = Size::new(available, height);
                 let pod = Regions::one(size, Axes::splat(false));
                 let frame = cell.measure(engine, self.styles, pod)?.into_frame();
                 resolved.set_max(frame.width() - already_covered_width);
             }
 
+            if let Some(min) = self.column_min(x) {
+                resolved.set_max(min);
+            }
+
             self.rcols[x] = resolved;
             auto += resolved;
             count += 1;
@@ -1358,20 +2093,153 @@ impl<'a> GridLayouter<'a> {
     }
 
     /// Distribute remaining space to fractional columns.
-    fn grow_fractional_columns(&mut self, remaining: Abs, fr: Fr) {
+    fn grow_fractional_columns(&mut self, mut remaining: Abs, mut fr: Fr) {
         if fr.is_zero() {
             return;
         }
 
-        for (&col, rcol) in self.grid.cols.iter().zip(&mut self.rcols) {
+        // Fractional columns with a lower bound already received it. They
+        // give it back to the remaining space, but keep it if their share
+        // turns out smaller. Fixing one column at its lower bound leaves less
+        // space for the others, so we repeat until nothing changes.
+        let mut fixed = vec![false; self.rcols.len()];
+        for x in 0..self.rcols.len() {
+            if self.grid.cols[x].is_fractional() && self.column_min(x).is_some() {
+                remaining += self.rcols[x];
+            }
+        }
+
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for x in 0..self.rcols.len() {
+                let Sizing::Fr(v) = self.grid.cols[x] else { continue };
+                let Some(min) = self.column_min(x) else { continue };
+                if !fixed[x] && v.share(fr, remaining) < min {
+                    fixed[x] = true;
+                    remaining -= min;
+                    fr = fr - v;
+                    changed = true;
+                }
+            }
+        }
+
+        for (x, (&col, rcol)) in self.grid.cols.iter().zip(&mut self.rcols).enumerate() {
             if let Sizing::Fr(v) = col {
-                *rcol = v.share(fr, remaining);
+                if !fixed[x] {
+                    *rcol = v.share(fr, remaining);
+                }
             }
         }
     }
 
     /// Redistribute space to auto columns so that each gets a fair share.
+    ///
+    /// Pinned columns keep their measured width, so only the other auto
+    /// columns give up space. If the pinned columns alone don't fit, they are
+    /// shrunk proportionally as a last resort and the others only keep their
+    /// lower bounds.
     fn shrink_auto_columns(&mut self, available: Abs, count: usize) {
+        let grid = self.grid;
+        let pinned = |x: usize| grid.cols[x] == Sizing::Auto && grid.is_pinned(x);
+        let pinned_count = (0..grid.cols.len()).filter(|&x| pinned(x)).count();
+        if pinned_count == 0 {
+            self.shrink_columns_fairly(available, count, |_| true);
+            return;
+        }
+
+        let pinned_width: Abs = (0..grid.cols.len())
+            .filter(|&x| pinned(x))
+            .map(|x| self.rcols[x])
+            .sum();
+
+        if pinned_width <= available {
+            self.shrink_columns_fairly(
+                available - pinned_width,
+                count - pinned_count,
+                |x| !pinned(x),
+            );
+            return;
+        }
+
+        let factor = available / pinned_width;
+        for x in 0..self.rcols.len() {
+            if grid.cols[x] == Sizing::Auto {
+                let width = if pinned(x) { self.rcols[x] * factor } else { Abs::zero() };
+                self.rcols[x] = width.max(self.column_min(x).unwrap_or_default());
+            }
+        }
+    }
+
+    /// Shrink the eligible auto columns such that each gets a fair share of
+    /// the available space, but never less than its lower bound.
+    fn shrink_columns_fairly(
+        &mut self,
+        available: Abs,
+        count: usize,
+        eligible: impl Fn(usize) -> bool,
+    ) {
+        // Without eligible columns, there is no fair share to compute, and
+        // the columns keep their sizes.
+        if count == 0 {
+            return;
+        }
+
+        let grid = self.grid;
+        let mut floors = vec![None; self.rcols.len()];
+        let mut redistribute = available;
+        let mut overlarge = count;
+        let mut changed = true;
+        let mut fair = Abs::zero();
+
+        // Columns whose lower bound exceeds their fair share keep the bound
+        // instead. This leaves less space to the others, so their share is
+        // computed anew until no more columns hit their bound.
+        while changed {
+            changed = false;
+            fair = self.fair_share(redistribute, overlarge, |x| {
+                eligible(x) && floors[x].is_none()
+            });
+
+            for x in 0..self.rcols.len() {
+                if grid.cols[x] != Sizing::Auto || !eligible(x) || floors[x].is_some() {
+                    continue;
+                }
+
+                if let Some(min) = self.column_min(x).filter(|&min| min > fair) {
+                    floors[x] = Some(min);
+                    redistribute -= min;
+                    overlarge -= 1;
+                    changed = true;
+                }
+            }
+        }
+
+        // Redistribute space fairly among overlarge columns. They all receive
+        // exactly the same share, so overlarge columns with equal content end
+        // up with identical widths and there is no remainder to distribute.
+        // If the lower bounds alone take up all space, nothing remains.
+        let fair = fair.max(Abs::zero());
+        for (x, rcol) in self.rcols.iter_mut().enumerate() {
+            if grid.cols[x] == Sizing::Auto && eligible(x) {
+                if let Some(min) = floors[x] {
+                    *rcol = min;
+                } else if *rcol > fair {
+                    *rcol = fair;
+                }
+            }
+        }
+    }
+
+    /// The fair share of the available space for each of the eligible auto
+    /// columns. Columns that need less than that keep their width and leave
+    /// the rest to the others.
+    fn fair_share(
+        &self,
+        available: Abs,
+        count: usize,
+        eligible: impl Fn(usize) -> bool,
+    ) -> Abs {
         let mut last;
         let mut fair = -Abs::inf();
         let mut redistribute = available;
@@ -1384,10 +2252,10 @@ impl<'a> GridLayouter<'a> {
             last = fair;
             fair = redistribute / (overlarge as f64);
 
-            for (&col, &rcol) in self.grid.cols.iter().zip(&self.rcols) {
+            for (x, (&col, &rcol)) in self.grid.cols.iter().zip(&self.rcols).enumerate() {
                 // Remove an auto column if it is not overlarge (rcol <= fair),
                 // but also hasn't already been removed (rcol > last).
-                if col == Sizing::Auto && rcol <= fair && rcol > last {
+                if col == Sizing::Auto && eligible(x) && rcol <= fair && rcol > last {
                     redistribute -= rcol;
                     overlarge -= 1;
                     changed = true;
@@ -1395,12 +2263,7 @@ impl<'a> GridLayouter<'a> {
             }
         }
 
-        // Redistribute space fairly among overlarge columns.
-        for (&col, rcol) in self.grid.cols.iter().zip(&mut self.rcols) {
-            if col == Sizing::Auto && *rcol > fair {
-                *rcol = fair;
-            }
-        }
+        fair
     }
 
     /// Layout a row with automatic height. Such a row may break across multiple
@@ -1431,8 +2294,9 @@ impl<'a> GridLayouter<'a> {
         // Expand all but the last region.
         // Skip the first region if the space is eaten up by an fr row.
         let len = resolved.len();
+        let mut backlog = vec![];
         for (region, target) in self
-            .regions
+            .body_regions(&mut backlog)
             .iter()
             .zip(&mut resolved[..len - 1])
             .skip(self.lrows.iter().any(|row| matches!(row, Row::Fr(..))) as usize)
@@ -1462,13 +2326,18 @@ impl<'a> GridLayouter<'a> {
         can_skip: bool,
     ) -> SourceResult<Option<Vec<Abs>>> {
         let mut resolved: Vec<Abs> = vec![];
+        let mut backlog = vec![];
+        let regions = self.body_regions(&mut backlog);
 
         for x in 0..self.rcols.len() {
-            if let Some(cell) = self.grid.cell(x, y) {
-                let mut pod = self.regions;
+            if let Some(cell) = self.charged_cell(engine, x, y) {
+                let mut pod = regions;
                 pod.size.x = self.cell_spanned_width(x, cell.colspan.get());
 
-                let frames = cell.measure(engine, self.styles, pod)?.into_frames();
+                let mut frames = cell.measure(engine, self.styles, pod)?.into_frames();
+                for frame in &mut frames {
+                    self.fit_cell(frame, pod.size.x);
+                }
 
                 // Skip the first region if one cell in it is empty. Then,
                 // remeasure.
@@ -1538,7 +2407,7 @@ impl<'a> GridLayouter<'a> {
 
         // Reverse the column order when using RTL.
         for (x, &rcol) in self.rcols.iter().enumerate().rev_if(self.is_rtl) {
-            if let Some(cell) = self.grid.cell(x, y) {
+            if let Some(cell) = self.charged_cell(engine, x, y) {
                 let width = self.cell_spanned_width(x, cell.colspan.get());
                 let size = Size::new(width, height);
                 let mut pod = Regions::one(size, Axes::splat(true));
@@ -1546,6 +2415,7 @@ impl<'a> GridLayouter<'a> {
                     pod.full = self.regions.full;
                 }
                 let mut frame = cell.layout(engine, self.styles, pod)?.into_frame();
+                self.fit_cell(&mut frame, width);
                 if self.is_rtl {
                     // In the grid, cell colspans expand to the right,
                     // so we're at the leftmost (lowest 'x') column
@@ -1587,16 +2457,20 @@ impl<'a> GridLayouter<'a> {
         pod.full = self.regions.full;
         pod.backlog = &heights[1..];
 
-        // Layout the row.
+        // Layout the row. Each cell decides itself where its content may
+        // break. In particular, paragraphs keep their first and last two lines
+        // together, so a row never leaves a single line of a cell stranded in
+        // a region.
         let mut pos = Point::zero();
         for (x, &rcol) in self.rcols.iter().enumerate().rev_if(self.is_rtl) {
-            if let Some(cell) = self.grid.cell(x, y) {
+            if let Some(cell) = self.charged_cell(engine, x, y) {
                 let width = self.cell_spanned_width(x, cell.colspan.get());
                 pod.size.x = width;
 
                 // Push the layouted frames into the individual output frames.
                 let fragment = cell.layout(engine, self.styles, pod)?;
                 for (output, mut frame) in outputs.iter_mut().zip(fragment) {
+                    self.fit_cell(&mut frame, width);
                     if self.is_rtl {
                         let offset = Point::with_x(-width + rcol);
                         frame.translate(offset);
@@ -1625,16 +2499,35 @@ impl<'a> GridLayouter<'a> {
         for row in &self.lrows {
             match row {
                 Row::Frame(frame, _) => used += frame.height(),
-                Row::Fr(v, _) => fr += *v,
+                Row::Fr(v, _, _) => fr += *v,
             }
         }
 
+        // Fractional rows share the space left over in this region. This is
+        // based on the region's size when we started it rather than its full
+        // size, since the grid might not have started at the top of the region
+        // (in which case it would overflow). The space for the footer was
+        // already subtracted from the initial size when reserving it.
+        let remaining = self.initial.y.min(self.regions.full) - used;
+        let fr_heights = self.share_fractional_rows(remaining, fr);
+        let fr_height: Abs = fr_heights.iter().sum();
+
+        // Lay out the repeating footer if space was reserved for it.
+        let footer = match self.footer_height {
+            Some(_) => self.layout_footer(engine)?,
+            None => vec![],
+        };
+        let footer_height: Abs = footer.iter().map(|(frame, _)| frame.height()).sum();
+
         // Determine the size of the grid in this region, expanding fully if
-        // there are fr rows.
+        // there are fr rows. Otherwise, the grid hugs its content, even in
+        // finite regions. The footer is placed below everything else.
         let mut size = Size::new(self.width, used).min(self.initial);
         if fr.get() > 0.0 && self.initial.y.is_finite() {
             size.y = self.initial.y;
         }
+        size.y.set_max(used + fr_height);
+        size.y += footer_height;
 
         // The frame for the region.
         let mut output = Frame::soft(size);
@@ -1642,14 +2535,10 @@ impl<'a> GridLayouter<'a> {
         let mut rrows = vec![];
 
         // Place finished rows and layout fractional rows.
-        for row in std::mem::take(&mut self.lrows) {
+        for (row, height) in std::mem::take(&mut self.lrows).into_iter().zip(fr_heights) {
             let (frame, y) = match row {
                 Row::Frame(frame, y) => (frame, y),
-                Row::Fr(v, y) => {
-                    let remaining = self.regions.full - used;
-                    let height = v.share(fr, remaining);
-                    (self.layout_single_row(engine, height, y)?, y)
-                }
+                Row::Fr(_, y, _) => (self.layout_single_row(engine, height, y)?, y),
             };
 
             let height = frame.height();
@@ -1658,13 +2547,201 @@ impl<'a> GridLayouter<'a> {
             pos.y += height;
         }
 
-        self.finished.push(output);
-        self.rrows.push(rrows);
+        // Place the footer at the very bottom of the region.
+        pos.y = size.y - footer_height;
+        for (frame, y) in footer {
+            let height = frame.height();
+            output.push_frame(pos, frame);
+            rrows.push(RowPiece { height, y });
+            pos.y += height;
+        }
+
+        self.finished.push_back(output);
+        self.rrows.push_back(rrows);
         self.regions.next();
+        if self.repeat_footer {
+            self.reserve_footer(engine)?;
+        }
         self.initial = self.regions.size;
+        if self.repeat_header {
+            self.place_header(engine)?;
+        }
+
+        Ok(())
+    }
+
+    /// Places the repeating header at the top of the current region if it
+    /// fits there. Otherwise, the region is used for the body rows only, so
+    /// that a region which is too short for the header can't cause the grid
+    /// to skip regions forever.
+    fn place_header(&mut self, engine: &mut Engine) -> SourceResult<()> {
+        let Some(header) = self.grid.header else { return Ok(()) };
+        let rows = self.layout_unbreakable_rows(engine, 0..header.end)?;
+        let height: Abs = rows.iter().map(|(frame, _)| frame.height()).sum();
+        if self.regions.size.y.fits(height) {
+            for (frame, y) in rows {
+                self.push_row(frame, y);
+            }
+        }
+        Ok(())
+    }
+
+    /// Distribute the remaining space of the current region among its
+    /// fractional rows, yielding one height per row (zero for other rows).
+    ///
+    /// Rows whose share would be smaller than their content are fixed at the
+    /// content's height, and the other rows share what is left.
+    fn share_fractional_rows(&self, mut remaining: Abs, mut fr: Fr) -> Vec<Abs> {
+        let mut fixed = vec![false; self.lrows.len()];
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (row, fixed) in self.lrows.iter().zip(&mut fixed) {
+                if let Row::Fr(v, _, min) = *row {
+                    if !*fixed && v.share(fr, remaining) < min {
+                        *fixed = true;
+                        remaining -= min;
+                        fr = fr - v;
+                        changed = true;
+                    }
+                }
+            }
+        }
+
+        self.lrows
+            .iter()
+            .zip(fixed)
+            .map(|(row, fixed)| match *row {
+                Row::Fr(_, _, min) if fixed => min,
+                Row::Fr(v, _, _) => v.share(fr, remaining),
+                Row::Frame(..) => Abs::zero(),
+            })
+            .collect()
+    }
+
+    /// Reserves space for the repeating footer at the bottom of the current
+    /// region if it fits there.
+    fn reserve_footer(&mut self, engine: &mut Engine) -> SourceResult<()> {
+        let height: Abs = self
+            .layout_footer(engine)?
+            .iter()
+            .map(|(frame, _)| frame.height())
+            .sum();
+
+        if self.regions.size.y.fits(height) {
+            self.regions.size.y -= height;
+            self.footer_height = Some(height);
+        } else {
+            self.footer_height = None;
+        }
 
         Ok(())
     }
+
+    /// Layout the rows of the repeating footer. Each row is laid out into a
+    /// single region, as the footer can't break across regions.
+    fn layout_footer(
+        &mut self,
+        engine: &mut Engine,
+    ) -> SourceResult<Vec<(Frame, usize)>> {
+        let Some(footer) = self.grid.footer else { return Ok(vec![]) };
+        self.layout_unbreakable_rows(engine, footer.start..self.grid.rows.len())
+    }
+
+    /// Layout the given rows, each into a single region. Used for headers and
+    /// footers, which can't break across regions.
+    fn layout_unbreakable_rows(
+        &mut self,
+        engine: &mut Engine,
+        rows: std::ops::Range<usize>,
+    ) -> SourceResult<Vec<(Frame, usize)>> {
+        let mut frames = vec![];
+        for y in rows {
+            let height = match self.grid.rows[y] {
+                Sizing::Rel(v) => {
+                    v.resolve(self.styles).relative_to(self.regions.base().y)
+                }
+                // Fractional rows are sized like auto rows, since
+                // there is no remaining space to distribute to them.
+                Sizing::Auto | Sizing::Fr(_) => {
+                    self.measure_unbreakable_row(engine, y)?
+                }
+            };
+            frames.push((self.layout_single_row(engine, height, y)?, y));
+        }
+        Ok(frames)
+    }
+
+    /// Apply the grid's overflow policy to a frame of a cell with the given
+    /// width.
+    fn fit_cell(&self, frame: &mut Frame, width: Abs) {
+        match self.grid.overflow {
+            CellOverflow::Overflow => {}
+            CellOverflow::Clip => {
+                frame.clip(Path::rect(Size::new(width, frame.height())));
+            }
+            CellOverflow::Shrink => {
+                let extent = content_width(frame);
+                if extent > width && width > Abs::zero() {
+                    let factor = width / extent;
+                    let scale = Ratio::new(factor);
+                    frame.transform(Transform::scale(scale, scale));
+                    frame.set_size(Size::new(frame.width(), frame.height() * factor));
+                }
+            }
+            CellOverflow::Break => {
+                let extent = content_width(frame);
+                if extent > width && width > Abs::zero() {
+                    let lines = (extent / width).ceil() as usize;
+                    let height = frame.height();
+                    let size = Size::new(frame.width(), height * lines as f64);
+                    let mut output = Frame::soft(size);
+                    for i in 0..lines {
+                        let mut line = frame.clone();
+                        line.translate(Point::with_x(-width * i as f64));
+                        line.clip(Path::rect(Size::new(width, height)));
+                        output.push_frame(Point::with_y(height * i as f64), line);
+                    }
+                    *frame = output;
+                }
+            }
+        }
+    }
+
+    /// Measure the height of a row that can't break across regions.
+    fn measure_unbreakable_row(
+        &mut self,
+        engine: &mut Engine,
+        y: usize,
+    ) -> SourceResult<Abs> {
+        let mut height = Abs::zero();
+        for x in 0..self.rcols.len() {
+            if let Some(cell) = self.charged_cell(engine, x, y) {
+                let width = self.cell_spanned_width(x, cell.colspan.get());
+                let size = Size::new(width, self.regions.base().y);
+                let pod = Regions::one(size, Axes::new(true, false));
+                let mut frame = cell.measure(engine, self.styles, pod)?.into_frame();
+                self.fit_cell(&mut frame, width);
+                height.set_max(frame.height());
+            }
+        }
+        Ok(height)
+    }
+
+    /// The regions available to the body rows of the grid.
+    ///
+    /// This accounts for the space reserved by a repeating footer in the
+    /// following regions. In the current region, that space was already
+    /// subtracted when it was reserved.
+    fn body_regions<'v>(&self, backlog: &'v mut Vec<Abs>) -> Regions<'v> {
+        let footer = self.footer_height.unwrap_or_default();
+        let mut regions =
+            self.regions.map(backlog, |size| Size::new(size.x, size.y - footer));
+        regions.size = self.regions.size;
+        regions.full = self.regions.full;
+        regions.root = self.regions.root;
+        regions
+    }
 }
 
 /// Turn an iterator of extents into an iterator of offsets before, in between,
@@ -1676,3 +2753,220 @@ fn points(extents: impl IntoIterator<Item = Abs>) -> impl Iterator<Item = Abs> {
         offset
     })
 }
+
+/// The horizontal extent of a frame's contents, which exceeds the frame's
+/// width if the contents overflow it. Transforms are not taken into account.
+fn content_width(frame: &Frame) -> Abs {
+    frame.items().fold(frame.width(), |extent, (pos, item)| {
+        let width = match item {
+            FrameItem::Group(group) if group.clip_path.is_some() => group.frame.width(),
+            FrameItem::Group(group) => content_width(&group.frame),
+            FrameItem::Text(text) => text.width(),
+            FrameItem::Shape(shape, _) => shape.geometry.bbox_size().x,
+            FrameItem::Image(_, size, _) | FrameItem::Meta(_, size) => size.x,
+        };
+        extent.max(pos.x + width)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use comemo::{Prehashed, Track};
+
+    use super::*;
+    use crate::diag::{FileError, FileResult};
+    use crate::engine::Route;
+    use crate::eval::Tracer;
+    use crate::foundations::{Bytes, Datetime};
+    use crate::introspection::{Introspector, Locator};
+    use crate::syntax::{FileId, Source};
+    use crate::text::{Font, FontBook};
+    use crate::visualize::Color;
+    use crate::{Library, World};
+
+    /// A world without any files or fonts, which suffices to lay out empty
+    /// cells.
+    struct EmptyWorld {
+        library: Prehashed<Library>,
+        book: Prehashed<FontBook>,
+    }
+
+    impl World for EmptyWorld {
+        fn library(&self) -> &Prehashed<Library> {
+            &self.library
+        }
+
+        fn book(&self) -> &Prehashed<FontBook> {
+            &self.book
+        }
+
+        fn main(&self) -> Source {
+            Source::detached("")
+        }
+
+        fn source(&self, id: FileId) -> FileResult<Source> {
+            Err(FileError::NotFound(id.vpath().as_rootless_path().into()))
+        }
+
+        fn file(&self, id: FileId) -> FileResult<Bytes> {
+            Err(FileError::NotFound(id.vpath().as_rootless_path().into()))
+        }
+
+        fn font(&self, _: usize) -> Option<Font> {
+            None
+        }
+
+        fn today(&self, _: Option<i64>) -> Option<Datetime> {
+            None
+        }
+    }
+
+    /// Runs the given function with an engine for an empty world.
+    fn with_engine<T>(tracer: &mut Tracer, f: impl FnOnce(&mut Engine) -> T) -> T {
+        let world = EmptyWorld {
+            library: Prehashed::new(Library::default()),
+            book: Prehashed::new(FontBook::new()),
+        };
+        let world: &dyn World = &world;
+        let introspector = Introspector::default();
+        let mut locator = Locator::new();
+        let mut engine = Engine {
+            world: world.track(),
+            introspector: introspector.track(),
+            route: Route::default(),
+            locator: &mut locator,
+            tracer: tracer.track_mut(),
+        };
+        f(&mut engine)
+    }
+
+    /// Creates a grid with two rows of empty cells.
+    fn empty_grid(cols: &[Sizing]) -> CellGrid {
+        let cells = (0..2 * cols.len()).map(|_| Cell::from(Content::default()));
+        CellGrid::new(Axes::with_x(cols), Axes::default(), cells)
+    }
+
+    /// Lays out a grid in a single region.
+    fn layout_grid(engine: &mut Engine, grid: &CellGrid) -> Fragment {
+        let size = Size::new(Abs::pt(100.0), Abs::pt(100.0));
+        let regions = Regions::one(size, Axes::splat(false));
+        let styles = StyleChain::default();
+        GridLayouter::new(grid, regions, styles, Span::detached())
+            .layout(engine)
+            .unwrap()
+    }
+
+    /// Sizes the columns of a grid with two rows of empty cells and returns
+    /// how many cells were laid out to measure them.
+    fn measured_cells(cols: &[Sizing]) -> usize {
+        const BUDGET: usize = 100;
+
+        let grid = empty_grid(cols);
+        let mut tracer = Tracer::new();
+        tracer.set_budget(Some(BUDGET));
+        with_engine(&mut tracer, |engine| {
+            let size = Size::new(Abs::pt(100.0), Abs::pt(100.0));
+            let regions = Regions::one(size, Axes::splat(false));
+            let styles = StyleChain::default();
+            let mut layouter =
+                GridLayouter::new(&grid, regions, styles, Span::detached());
+            layouter.measure_columns(engine).unwrap();
+        });
+        BUDGET - tracer.remaining_budget().unwrap()
+    }
+
+    #[test]
+    fn test_columns_without_auto_are_not_measured() {
+        let fr = Sizing::Fr(Fr::one());
+        let rel = Sizing::Rel(Abs::pt(20.0).into());
+        assert_eq!(measured_cells(&[fr, rel, fr]), 0);
+        assert_eq!(measured_cells(&[Sizing::Auto, fr]), 2);
+        assert_eq!(measured_cells(&[Sizing::Auto, rel, Sizing::Auto]), 4);
+    }
+
+    #[test]
+    fn test_budget_is_shared_across_grids() {
+        let rel = Sizing::Rel(Abs::pt(20.0).into());
+        let grid = empty_grid(&[rel, rel]);
+        let mut tracer = Tracer::new();
+        tracer.set_budget(Some(10));
+
+        // Each of the four cells is measured for its auto row and then laid
+        // out.
+        with_engine(&mut tracer, |engine| layout_grid(engine, &grid));
+        assert_eq!(tracer.remaining_budget(), Some(2));
+        assert!(tracer.delayed().is_empty());
+
+        // The second grid runs out of budget, but still yields its region.
+        let fragment = with_engine(&mut tracer, |engine| layout_grid(engine, &grid));
+        assert_eq!(fragment.len(), 1);
+        assert_eq!(tracer.remaining_budget(), Some(0));
+        let errors = tracer.delayed();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].message, "grid layout exceeded the budget for cell layouts");
+    }
+
+    #[test]
+    fn test_regions_are_yielded_as_they_finish() {
+        // A repeated header row, six body rows and a repeated footer row, of
+        // which the header, three body rows and the footer fit into a region.
+        let rows = [Sizing::Rel(Abs::pt(20.0).into()); 8];
+        let cols = [Sizing::Rel(Abs::pt(20.0).into())];
+        let cells = (0..rows.len()).map(|_| Cell::from(Content::default()));
+        let mut grid =
+            CellGrid::new(Axes::new(&cols[..], &rows[..]), Axes::default(), cells);
+        grid.header = Some(Header { end: 1, repeated: true });
+        grid.footer = Some(Footer { start: 7, repeated: true });
+
+        let mut tracer = Tracer::new();
+        with_engine(&mut tracer, |engine| {
+            let size = Size::new(Abs::pt(100.0), Abs::pt(100.0));
+            let regions = Regions::repeat(size, Axes::splat(false));
+            let styles = StyleChain::default();
+            let layouter = GridLayouter::new(&grid, regions, styles, Span::detached());
+            let mut regions = layouter.layout_regions(engine);
+
+            // The first region is yielded before the rows of the second one
+            // and the footer are laid out.
+            let first = regions.next().unwrap().unwrap();
+            assert_eq!(first.height(), Abs::pt(100.0));
+            assert!(matches!(regions.stage, Stage::Row(y) if y < 7));
+
+            // The second region repeats the header and the footer.
+            let second = regions.next().unwrap().unwrap();
+            assert_eq!(second.height(), Abs::pt(100.0));
+            assert!(regions.next().is_none());
+        });
+    }
+
+    #[test]
+    fn test_row_fill_covers_gutter_between_equal_bands() {
+        let cells = (0..6).map(|_| Cell::from(Content::default()));
+        let tracks = Axes::with_x(&[Sizing::Auto; 2][..]);
+        let gutter = Axes::splat(&[Sizing::Auto][..]);
+        let mut grid = CellGrid::new(tracks, gutter, cells);
+        let [a, b] = [Color::RED, Color::BLUE].map(Paint::Solid);
+        grid.row_fills = vec![Some(a.clone()), Some(a.clone()), Some(b.clone())];
+
+        // Three content rows with two gutter rows in between.
+        assert_eq!(grid.rows.len(), 5);
+        assert_eq!(grid.row_fill(0), Some(&a));
+        assert_eq!(grid.row_fill(1), Some(&a));
+        assert_eq!(grid.row_fill(2), Some(&a));
+        assert_eq!(grid.row_fill(3), None);
+        assert_eq!(grid.row_fill(4), Some(&b));
+    }
+
+    #[test]
+    fn test_row_fill_without_gutter() {
+        let cells = (0..4).map(|_| Cell::from(Content::default()));
+        let tracks = Axes::with_x(&[Sizing::Auto; 2][..]);
+        let mut grid = CellGrid::new(tracks, Axes::default(), cells);
+        let a = Paint::Solid(Color::RED);
+        grid.row_fills = vec![None, Some(a.clone())];
+
+        assert_eq!(grid.row_fill(0), None);
+        assert_eq!(grid.row_fill(1), Some(&a));
+        assert_eq!(grid.row_fill(2), None);
+    }
+}
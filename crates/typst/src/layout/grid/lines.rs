@@ -534,6 +534,7 @@ mod test {
             colspan: NonZeroUsize::ONE,
             stroke: Sides::splat(Some(Arc::new(Stroke::default()))),
             stroke_overridden: Sides::splat(false),
+            empty: false,
         }
     }
 
@@ -544,6 +545,7 @@ mod test {
             colspan: NonZeroUsize::try_from(colspan).unwrap(),
             stroke: Sides::splat(Some(Arc::new(Stroke::default()))),
             stroke_overridden: Sides::splat(false),
+            empty: false,
         }
     }
 
@@ -1,29 +1,37 @@
 mod layout;
 mod lines;
 
-pub use self::layout::{Cell, CellGrid, Celled, GridItem, GridLayouter, ResolvableCell};
+pub use self::layout::{
+    Cell, CellGrid, CellOverflow, Celled, GridItem, GridLayouter, GridRegions,
+    ResolvableCell, RowGroup,
+};
 pub use self::lines::LinePosition;
 
 use std::num::NonZeroUsize;
 use std::sync::Arc;
 
+use comemo::{Track, Tracked, TrackedMut};
 use ecow::eco_format;
 use smallvec::{smallvec, SmallVec};
 
 use crate::diag::{bail, SourceResult, StrResult, Trace, Tracepoint};
-use crate::engine::Engine;
+use crate::engine::{Engine, Route};
+use crate::eval::Tracer;
 use crate::foundations::{
-    cast, elem, scope, Array, Content, Fold, Packed, Show, Smart, StyleChain, Value,
+    cast, dict, elem, scope, Array, Content, Dict, Fold, Packed, Show, Smart, StyleChain,
+    Value,
 };
+use crate::introspection::{Introspector, Locator};
 use crate::layout::{
-    Abs, AlignElem, Alignment, Axes, Dir, Fragment, LayoutMultiple, Length,
-    OuterHAlignment, OuterVAlignment, Regions, Rel, Sides, Sizing,
+    Abs, AlignElem, Alignment, Angle, Axes, Dir, Fragment, LayoutMultiple, Length,
+    OuterHAlignment, OuterVAlignment, Regions, Rel, RotateElem, Sides, Sizing,
 };
-use crate::model::{TableCell, TableHLine, TableVLine};
+use crate::model::{TableCell, TableFooter, TableHLine, TableHeader, TableVLine};
 use crate::syntax::Span;
 use crate::text::TextElem;
-use crate::util::NonZeroExt;
+use crate::util::{NonZeroExt, Numeric};
 use crate::visualize::{Paint, Stroke};
+use crate::World;
 
 /// Arranges content in a grid.
 ///
@@ -51,6 +59,11 @@ use crate::visualize::{Paint, Stroke};
 ///   with a fraction of `{1fr}`, they will each take up half of the remaining
 ///   space.
 ///
+/// Columns additionally accept a dictionary with a `min` length and an
+/// optional `max` track size (e.g. `{(min: 3cm, max: 1fr)}`). Such a column is
+/// sized according to `max` (defaulting to `{auto}`), but never narrower than
+/// `min`, even if there isn't enough space.
+///
 /// To specify a single track, the array can be omitted in favor of a single
 /// value. To specify multiple `{auto}` tracks, enter the number of tracks
 /// instead of an array. For example, `columns:` `{3}` is equivalent to
@@ -169,7 +182,7 @@ pub struct GridElem {
     /// gutters, providing a single track size will only ever create a single
     /// column.
     #[borrowed]
-    pub columns: TrackSizings,
+    pub columns: ColumnSizings,
 
     /// The row sizes.
     ///
@@ -197,6 +210,94 @@ pub struct GridElem {
     #[borrowed]
     pub row_gutter: TrackSizings,
 
+    /// Whether to collapse gutters next to empty cells.
+    ///
+    /// When enabled, a gap between two columns is removed if, in every row,
+    /// the cell on at least one of its sides is empty, such that the
+    /// remaining cells sit closer together. Gaps between rows collapse in the
+    /// same way.
+    ///
+    /// ```example
+    /// #grid(
+    ///   columns: 3,
+    ///   gutter: 8pt,
+    ///   collapse-gutter: true,
+    ///   [A], [], [C],
+    /// )
+    /// ```
+    #[default(false)]
+    pub collapse_gutter: bool,
+
+    /// Columns that keep their natural width when the grid is too wide.
+    ///
+    /// If auto columns don't fit, they are usually all shrunk. Pinned columns
+    /// (given by their zero-based index) are left out, so only the other auto
+    /// columns give up space. The pinned columns are only shrunk as well if
+    /// they don't fit on their own.
+    ///
+    /// ```example
+    /// #grid(
+    ///   columns: 2,
+    ///   pinned-columns: (0,),
+    ///   [*Description*], lorem(20),
+    /// )
+    /// ```
+    #[borrowed]
+    pub pinned_columns: Vec<usize>,
+
+    /// Auto columns that should end up with the same width.
+    ///
+    /// After the auto columns are measured and, if necessary, shrunk, the
+    /// listed columns (given by their zero-based index) are all set to the
+    /// average of their widths. This keeps the grid's total width unchanged.
+    /// Any rounding remainder is left unused at the end of the grid, such
+    /// that the listed columns are exactly equal and the grid never becomes
+    /// wider than the available space.
+    ///
+    /// ```example
+    /// #grid(
+    ///   columns: 2,
+    ///   equal-columns: (0, 1),
+    ///   [Short], lorem(4),
+    /// )
+    /// ```
+    #[borrowed]
+    pub equal_columns: Vec<usize>,
+
+    /// How to handle cell content that is too wide for its cell.
+    ///
+    /// - `{"clip"}`: The content is cut off at the cell's edges.
+    /// - `{"shrink"}`: The content is scaled down until it fits.
+    /// - `{"break"}`: The content wraps onto additional lines, each as wide as
+    ///   the cell.
+    /// - `{"overflow"}`: The content bleeds past the cell's edges (default).
+    ///
+    /// ```example
+    /// #grid(
+    ///   columns: (40pt, 40pt),
+    ///   overflow: "shrink",
+    ///   rect(width: 80pt), [B],
+    /// )
+    /// ```
+    pub overflow: CellOverflow,
+
+    /// Groups of rows that should stay together.
+    ///
+    /// Each group is given as an array of its first row and the row after its
+    /// last one (both zero-based). If a group doesn't fit into the remaining
+    /// space of a page, it moves to the next page as a whole. A group that is
+    /// too large for a page on its own breaks like regular rows.
+    ///
+    /// ```example
+    /// #set page(height: 80pt)
+    /// #grid(
+    ///   row-groups: ((1, 3),),
+    ///   [A], [B], [C],
+    /// )
+    /// ```
+    #[borrowed]
+    pub row_groups: Vec<RowGroup>,
+
     /// How to fill the cells.
     ///
     /// This can be a color or a function that returns a color. The function is
@@ -217,6 +318,29 @@ pub struct GridElem {
     #[borrowed]
     pub fill: Celled<Option<Paint>>,
 
+    /// How to fill whole rows.
+    ///
+    /// Unlike with `fill`, each row is filled with one continuous band that
+    /// also covers the gaps between columns, which is useful for banded
+    /// grids. This can be a color, an array of colors that is cycled through
+    /// the rows, or a function that is passed the row index, starting at zero,
+    /// and returns a color. A gap between two rows is filled if both rows
+    /// have the same band. Cell fills are drawn on top of row bands.
+    ///
+    /// ```example
+    /// #grid(
+    ///   columns: 3,
+    ///   gutter: 4pt,
+    ///   inset: 4pt,
+    ///   row-fill: (none, luma(230)),
+    ///   [A], [B], [C],
+    ///   [D], [E], [F],
+    ///   [G], [H], [I],
+    /// )
+    /// ```
+    #[borrowed]
+    pub row_fill: Celled<Option<Paint>>,
+
     /// How to align the cells' content.
     ///
     /// This can either be a single alignment, an array of alignments
@@ -292,6 +416,12 @@ impl GridElem {
 
     #[elem]
     type GridVLine;
+
+    #[elem]
+    type GridHeader;
+
+    #[elem]
+    type GridFooter;
 }
 
 impl LayoutMultiple for Packed<GridElem> {
@@ -302,20 +432,60 @@ impl LayoutMultiple for Packed<GridElem> {
         styles: StyleChain,
         regions: Regions,
     ) -> SourceResult<Fragment> {
-        let inset = self.inset(styles);
-        let align = self.align(styles);
-        let columns = self.columns(styles);
-        let rows = self.rows(styles);
-        let column_gutter = self.column_gutter(styles);
-        let row_gutter = self.row_gutter(styles);
-        let fill = self.fill(styles);
-        let stroke = self.stroke(styles);
-
-        let tracks = Axes::new(columns.0.as_slice(), rows.0.as_slice());
+        let grid = resolve_grid(self, engine, styles)?;
+        let layouter = GridLayouter::new(&grid, regions, styles, self.span());
+
+        // Measure the columns and layout the grid row-by-row.
+        layouter.layout(engine)
+    }
+}
+
+/// Resolves the cells, tracks and lines of the grid.
+///
+/// This is memoized, so that laying out the same grid into different regions,
+/// e.g. when it is measured before being placed, reuses the resolved cells.
+/// Since these keep their cached hashes, the memoized layouts of the cells
+/// don't have to hash them again.
+fn resolve_grid(
+    elem: &Packed<GridElem>,
+    engine: &mut Engine,
+    styles: StyleChain,
+) -> SourceResult<Arc<CellGrid>> {
+    #[comemo::memoize]
+    fn cached(
+        elem: &Packed<GridElem>,
+        world: Tracked<dyn World + '_>,
+        introspector: Tracked<Introspector>,
+        route: Tracked<Route>,
+        locator: Tracked<Locator>,
+        tracer: TrackedMut<Tracer>,
+        styles: StyleChain,
+    ) -> SourceResult<Arc<CellGrid>> {
+        let mut locator = Locator::chained(locator);
+        let mut engine = Engine {
+            world,
+            introspector,
+            route: Route::extend(route).unnested(),
+            locator: &mut locator,
+            tracer,
+        };
+
+        let inset = elem.inset(styles);
+        let align = elem.align(styles);
+        let columns = elem.columns(styles);
+        let rows = elem.rows(styles);
+        let column_gutter = elem.column_gutter(styles);
+        let row_gutter = elem.row_gutter(styles);
+        let fill = elem.fill(styles);
+        let row_fill = elem.row_fill(styles);
+        let stroke = elem.stroke(styles);
+
+        let sizings = columns.sizings();
+        let tracks = Axes::new(sizings.as_slice(), rows.0.as_slice());
         let gutter = Axes::new(column_gutter.0.as_slice(), row_gutter.0.as_slice());
         // Use trace to link back to the grid when a specific cell errors
         let tracepoint = || Tracepoint::Call(Some(eco_format!("grid")));
-        let items = self.children().iter().map(|child| match child {
+        let items = elem.children().iter().map(|child| match child {
             GridChild::HLine(hline) => GridItem::HLine {
                 y: hline.y(styles),
                 start: hline.start(styles),
@@ -346,27 +516,63 @@ impl LayoutMultiple for Packed<GridElem> {
                     OuterHAlignment::End | OuterHAlignment::Right => LinePosition::After,
                 },
             },
+            GridChild::Header(header) => GridItem::Header {
+                repeat: header.repeat(styles),
+                span: header.span(),
+                cells: header
+                    .children()
+                    .iter()
+                    .map(|cell| Packed::new(cell.clone()).spanned(header.span()))
+                    .collect(),
+            },
+            GridChild::Footer(footer) => GridItem::Footer {
+                repeat: footer.repeat(styles),
+                span: footer.span(),
+                cells: footer
+                    .children()
+                    .iter()
+                    .map(|cell| Packed::new(cell.clone()).spanned(footer.span()))
+                    .collect(),
+            },
             GridChild::Cell(cell) => GridItem::Cell(cell.clone()),
         });
-        let grid = CellGrid::resolve(
+        let mut grid = CellGrid::resolve(
             tracks,
             gutter,
             items,
             fill,
+            row_fill,
             align,
             &inset,
             &stroke,
-            engine,
+            &mut engine,
             styles,
-            self.span(),
+            elem.span(),
         )
-        .trace(engine.world, tracepoint, self.span())?;
+        .trace(engine.world, tracepoint, elem.span())?;
 
-        let layouter = GridLayouter::new(&grid, regions, styles, self.span());
+        if elem.collapse_gutter(styles) {
+            grid.collapse_gutter();
+        }
 
-        // Measure the columns and layout the grid row-by-row.
-        layouter.layout(engine)
+        grid.set_column_mins(&columns.mins());
+        grid.pin_columns(elem.pinned_columns(styles), elem.span())?;
+        grid.equalize_columns(elem.equal_columns(styles), elem.span())?;
+        grid.set_overflow(elem.overflow(styles));
+        grid.group_rows(elem.row_groups(styles), elem.span())?;
+
+        Ok(Arc::new(grid))
     }
+
+    cached(
+        elem,
+        engine.world,
+        engine.introspector,
+        engine.route.track(),
+        engine.locator.track(),
+        TrackedMut::reborrow_mut(&mut engine.tracer),
+        styles,
+    )
 }
 
 /// Track sizing definitions.
@@ -381,11 +587,68 @@ cast! {
     values: Array => Self(values.into_iter().map(Value::cast).collect::<StrResult<_>>()?),
 }
 
+/// Column sizing definitions, which may give columns a lower bound.
+#[derive(Debug, Default, Clone, Eq, PartialEq, Hash)]
+pub struct ColumnSizings(pub SmallVec<[ColumnSizing; 4]>);
+
+impl ColumnSizings {
+    /// The sizings of the columns, without their lower bounds.
+    pub fn sizings(&self) -> SmallVec<[Sizing; 4]> {
+        self.0.iter().map(|column| column.sizing).collect()
+    }
+
+    /// The lower bounds of the columns.
+    pub fn mins(&self) -> SmallVec<[Option<Rel<Length>>; 4]> {
+        self.0.iter().map(|column| column.min).collect()
+    }
+}
+
+cast! {
+    ColumnSizings,
+    self => self.0.into_value(),
+    column: ColumnSizing => Self(smallvec![column]),
+    count: NonZeroUsize => Self(smallvec![Sizing::Auto.into(); count.get()]),
+    values: Array => Self(values.into_iter().map(Value::cast).collect::<StrResult<_>>()?),
+}
+
+/// The sizing of a single column.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct ColumnSizing {
+    /// How to size the column. If the column has a lower bound, this is its
+    /// upper bound.
+    pub sizing: Sizing,
+    /// The width below which the column never shrinks, if any.
+    pub min: Option<Rel<Length>>,
+}
+
+impl From<Sizing> for ColumnSizing {
+    fn from(sizing: Sizing) -> Self {
+        Self { sizing, min: None }
+    }
+}
+
+cast! {
+    ColumnSizing,
+    self => match self.min {
+        Some(min) => dict! { "min" => min, "max" => self.sizing }.into_value(),
+        None => self.sizing.into_value(),
+    },
+    sizing: Sizing => sizing.into(),
+    mut dict: Dict => {
+        let min = dict.take("min")?.cast()?;
+        let sizing = dict.take("max").ok().map(Value::cast).transpose()?;
+        dict.finish(&["min", "max"])?;
+        Self { sizing: sizing.unwrap_or_default(), min: Some(min) }
+    },
+}
+
 /// Any child of a grid element.
 #[derive(Debug, PartialEq, Clone, Hash)]
 pub enum GridChild {
     HLine(Packed<GridHLine>),
     VLine(Packed<GridVLine>),
+    Header(Packed<GridHeader>),
+    Footer(Packed<GridFooter>),
     Cell(Packed<GridCell>),
 }
 
@@ -394,6 +657,8 @@ cast! {
     self => match self {
         Self::HLine(hline) => hline.into_value(),
         Self::VLine(vline) => vline.into_value(),
+        Self::Header(header) => header.into_value(),
+        Self::Footer(footer) => footer.into_value(),
         Self::Cell(cell) => cell.into_value(),
     },
     v: Content => {
@@ -412,6 +677,16 @@ cast! {
                 "cannot use `table.vline` as a grid line; use `grid.vline` instead"
             );
         }
+        if v.is::<TableHeader>() {
+            bail!(
+                "cannot use `table.header` as a grid header; use `grid.header` instead"
+            );
+        }
+        if v.is::<TableFooter>() {
+            bail!(
+                "cannot use `table.footer` as a grid footer; use `grid.footer` instead"
+            );
+        }
         v.into()
     }
 }
@@ -422,6 +697,8 @@ impl From<Content> for GridChild {
             .into_packed::<GridHLine>()
             .map(GridChild::HLine)
             .or_else(|value| value.into_packed::<GridVLine>().map(GridChild::VLine))
+            .or_else(|value| value.into_packed::<GridHeader>().map(GridChild::Header))
+            .or_else(|value| value.into_packed::<GridFooter>().map(GridChild::Footer))
             .or_else(|value| value.into_packed::<GridCell>().map(GridChild::Cell))
             .unwrap_or_else(|value| {
                 let span = value.span();
@@ -543,6 +820,70 @@ pub struct GridVLine {
     pub position: OuterHAlignment,
 }
 
+/// A repeatable grid header.
+///
+/// The header's cells are placed in the first rows of the grid, so it must
+/// come before all other cells. By default, the header is repeated at the top
+/// of each page (or, more generally, region) the grid spans, which keeps long
+/// grids readable.
+///
+/// ```example
+/// #set page(height: 8em)
+/// #grid(
+///   columns: 2,
+///   grid.header(
+///     [*Left*], [*Right*],
+///   ),
+///   ..range(8).map(str),
+/// )
+/// ```
+#[elem(name = "header", title = "Grid Header")]
+pub struct GridHeader {
+    /// Whether this header should be repeated at the top of each region the
+    /// grid spans. If `{false}`, it is only placed once at the very start of
+    /// the grid.
+    ///
+    /// The header is not repeated in regions which are too short to hold it.
+    #[default(true)]
+    pub repeat: bool,
+
+    /// The cells of the header. They are always positioned automatically, so
+    /// they can't have explicit `x` or `y` coordinates.
+    #[variadic]
+    pub children: Vec<GridCell>,
+}
+
+/// A repeatable grid footer.
+///
+/// The footer's cells are placed in new rows at the bottom of the grid, below
+/// all other cells. By default, the footer is repeated at the bottom of each
+/// page (or, more generally, region) the grid spans, which is useful for
+/// column totals or "continued on the next page" notices.
+///
+/// ```example
+/// #set page(height: 8em)
+/// #grid(
+///   columns: 2,
+///   ..range(8).map(str),
+///   grid.footer(
+///     [*Left*], [*Right*],
+///   ),
+/// )
+/// ```
+#[elem(name = "footer", title = "Grid Footer")]
+pub struct GridFooter {
+    /// Whether this footer should be repeated at the bottom of each region
+    /// the grid spans. If `{false}`, it is only placed once at the very end
+    /// of the grid.
+    #[default(true)]
+    pub repeat: bool,
+
+    /// The cells of the footer. They are always positioned automatically, so
+    /// they can't have explicit `x` or `y` coordinates.
+    #[variadic]
+    pub children: Vec<GridCell>,
+}
+
 /// A cell in the grid. Use this to either override grid properties for a
 /// particular cell, or in show rules to apply certain styles to multiple cells
 /// at once.
@@ -619,7 +960,22 @@ pub struct GridCell {
     ///   [1], grid.cell(x: 3)[4], [2],
     /// )
     /// ```
-    pub x: Smart<usize>,
+    ///
+    /// A negative column is counted from the end, so `{-1}` refers to the
+    /// last column regardless of how many columns there are. A cell with a
+    /// colspan spans to the right of its column, so a cell with `{x: -2}` and
+    /// `{colspan: 2}` covers the last two columns. Once the cell is placed,
+    /// this field holds its actual, non-negative column.
+    ///
+    /// ```example
+    /// #grid(
+    ///   columns: 3,
+    ///   gutter: 3pt,
+    ///   grid.cell(x: -1)[Total],
+    ///   [A], [B],
+    /// )
+    /// ```
+    pub x: Smart<isize>,
 
     /// The cell's row (zero-indexed).
     /// This field may be used in show rules to style a cell depending on its
@@ -657,6 +1013,24 @@ pub struct GridCell {
     #[resolve]
     #[fold]
     pub stroke: Sides<Option<Option<Arc<Stroke>>>>,
+    /// How much to rotate the cell's content.
+    ///
+    /// The cell is sized to fit the rotated content, so a header rotated by
+    /// `{90deg}` contributes the height of its text to the column's width and
+    /// the width of its text to the row's height. This is useful for narrow
+    /// columns with long headers.
+    ///
+    /// ```example
+    /// #grid(
+    ///   columns: 3,
+    ///   gutter: 4pt,
+    ///   grid.cell(rotate: -90deg)[Apples],
+    ///   grid.cell(rotate: -90deg)[Pears],
+    ///   grid.cell(rotate: -90deg)[Plums],
+    ///   [3], [5], [2],
+    /// )
+    /// ```
+    pub rotate: Angle,
 }
 
 cast! {
@@ -699,7 +1073,7 @@ impl ResolvableCell for Packed<GridCell> {
         // cell stroke is the same as specifying 'none', so we equate the two
         // concepts.
         let stroke = cell_stroke.fold(stroke).map(Option::flatten);
-        cell.push_x(Smart::Custom(x));
+        cell.push_x(Smart::Custom(x as isize));
         cell.push_y(Smart::Custom(y));
         cell.push_fill(Smart::Custom(fill.clone()));
         cell.push_align(match align {
@@ -728,6 +1102,7 @@ impl ResolvableCell for Packed<GridCell> {
             }),
         );
         Cell {
+            empty: self.body().is_empty(),
             body: self.pack(),
             fill,
             colspan,
@@ -736,7 +1111,7 @@ impl ResolvableCell for Packed<GridCell> {
         }
     }
 
-    fn x(&self, styles: StyleChain) -> Smart<usize> {
+    fn x(&self, styles: StyleChain) -> Smart<isize> {
         (**self).x(styles)
     }
 
@@ -755,7 +1130,12 @@ impl ResolvableCell for Packed<GridCell> {
 
 impl Show for Packed<GridCell> {
     fn show(&self, _engine: &mut Engine, styles: StyleChain) -> SourceResult<Content> {
-        show_grid_cell(self.body().clone(), self.inset(styles), self.align(styles))
+        show_grid_cell(
+            self.body().clone(),
+            self.inset(styles),
+            self.align(styles),
+            self.rotate(styles),
+        )
     }
 }
 
@@ -771,9 +1151,15 @@ pub fn show_grid_cell(
     mut body: Content,
     inset: Smart<Sides<Option<Rel<Length>>>>,
     align: Smart<Alignment>,
+    rotate: Angle,
 ) -> SourceResult<Content> {
     let inset = inset.unwrap_or_default().map(Option::unwrap_or_default);
 
+    if !rotate.is_zero() {
+        // Reflow, such that the cell is measured with the rotated size.
+        body = RotateElem::new(body).with_angle(rotate).with_reflow(true).pack();
+    }
+
     if inset != Sides::default() {
         // Only pad if some inset is not 0pt.
         // Avoids a bug where using .padded() in any way inside Show causes
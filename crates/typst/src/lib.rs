@@ -123,8 +123,9 @@ fn typeset(
     loop {
         let _scope = TimingScope::new(ITER_NAMES[iter], None);
 
-        // Clear delayed errors.
+        // Clear delayed errors and restore the layout budget.
         tracer.delayed();
+        tracer.reset_budget();
 
         let constraint = <Introspector as Validate>::Constraint::new();
         let mut locator = Locator::new();
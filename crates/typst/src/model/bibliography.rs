@@ -29,8 +29,8 @@ use crate::foundations::{
 };
 use crate::introspection::{Introspector, Locatable, Location};
 use crate::layout::{
-    BlockElem, Em, GridCell, GridChild, GridElem, HElem, PadElem, Sizing, TrackSizings,
-    VElem,
+    BlockElem, ColumnSizings, Em, GridCell, GridChild, GridElem, HElem, PadElem, Sizing,
+    TrackSizings, VElem,
 };
 use crate::model::{
     CitationForm, CiteGroup, Destination, FootnoteElem, HeadingElem, LinkElem, ParElem,
@@ -250,7 +250,7 @@ impl Show for Packed<BibliographyElem> {
             seq.push(VElem::new(row_gutter).with_weakness(3).pack());
             seq.push(
                 GridElem::new(cells)
-                    .with_columns(TrackSizings(smallvec![Sizing::Auto; 2]))
+                    .with_columns(ColumnSizings(smallvec![Sizing::Auto.into(); 2]))
                     .with_column_gutter(TrackSizings(smallvec![COLUMN_GUTTER.into()]))
                     .with_row_gutter(TrackSizings(smallvec![(row_gutter).into()]))
                     .pack()
@@ -951,7 +951,7 @@ impl ElemRenderer<'_> {
                 GridChild::Cell(Packed::new(GridCell::new(prefix)).spanned(self.span)),
                 GridChild::Cell(Packed::new(GridCell::new(content)).spanned(self.span)),
             ])
-            .with_columns(TrackSizings(smallvec![Sizing::Auto; 2]))
+            .with_columns(ColumnSizings(smallvec![Sizing::Auto.into(); 2]))
             .with_column_gutter(TrackSizings(smallvec![COLUMN_GUTTER.into()]))
             .pack()
             .spanned(self.span);
@@ -6,8 +6,10 @@ use crate::foundations::{
     cast, elem, Args, Array, Construct, Content, Datetime, Packed, Smart, StyleChain,
     StyledElem, Value,
 };
-use crate::introspection::{Introspector, ManualPageCounter};
-use crate::layout::{LayoutRoot, Page, PageElem};
+use crate::introspection::{Introspector, Location, ManualPageCounter, Meta};
+use crate::layout::{Frame, FrameItem, LayoutRoot, Page, PageElem};
+use crate::model::Destination;
+use crate::util::hash128;
 
 /// The root element of a document and its metadata.
 ///
@@ -152,9 +154,162 @@ pub struct Document {
     pub introspector: Introspector,
 }
 
+impl Document {
+    /// Concatenates separately compiled documents into one, e.g. to export a
+    /// cover, body and appendix as a single file.
+    ///
+    /// The metadata is taken from the first document. Page numbers continue
+    /// from one document to the next, and the introspector is rebuilt from
+    /// the merged pages, so that headings and links are found at their new
+    /// pages. As separately compiled documents can assign the same locations
+    /// to different elements, the locations of each following document are
+    /// made distinct from those of the others. Since fonts and images are
+    /// deduplicated during export, the ones shared between documents are only
+    /// embedded once.
+    pub fn concat(documents: impl IntoIterator<Item = Document>) -> Document {
+        let mut documents = documents.into_iter();
+        let Some(mut merged) = documents.next() else {
+            return Document::default();
+        };
+
+        for (i, document) in documents.enumerate() {
+            let last = merged.pages.last().map_or(0, |page| page.number);
+            let first = document.pages.first().map_or(1, |page| page.number);
+            merged.pages.extend(document.pages.into_iter().map(|mut page| {
+                page.number = (page.number + last + 1).saturating_sub(first);
+                disambiguate(&mut page.frame, i + 1);
+                page
+            }));
+        }
+
+        merged.introspector.rebuild(&merged.pages);
+        merged
+    }
+}
+
+/// Makes the locations of elements and link targets in a frame distinct from
+/// those of other documents by mixing the document's index into their hashes.
+fn disambiguate(frame: &mut Frame, index: usize) {
+    let remap = |loc: Location| Location { hash: hash128(&(loc.hash, index)), ..loc };
+    for (_, item) in frame.items_mut() {
+        match item {
+            FrameItem::Group(group) => disambiguate(&mut group.frame, index),
+            FrameItem::Meta(Meta::Elem(content), _) => {
+                if let Some(loc) = content.location() {
+                    content.set_location(remap(loc));
+                }
+            }
+            FrameItem::Meta(Meta::Link(Destination::Location(loc), _), _) => {
+                *loc = remap(*loc);
+            }
+            _ => {}
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::foundations::{Bytes, NativeElement};
+    use crate::layout::{Abs, Em, Point, Size};
+    use crate::model::HeadingElem;
+    use crate::syntax::Span;
+    use crate::text::{Font, Glyph, Lang, TextItem};
+    use crate::visualize::{Color, Paint};
+
+    #[test]
+    fn test_concat_continues_page_numbers() {
+        let page = |number| Page {
+            frame: Frame::soft(Size::zero()),
+            numbering: None,
+            number,
+        };
+        let document = |numbers: &[usize]| Document {
+            pages: numbers.iter().copied().map(page).collect(),
+            ..Default::default()
+        };
+
+        let merged =
+            Document::concat([document(&[1]), document(&[1, 2]), document(&[5, 6])]);
+        let numbers: Vec<_> = merged.pages.iter().map(|page| page.number).collect();
+        assert_eq!(numbers, [1, 2, 3, 4, 5]);
+        assert!(Document::concat(Vec::new()).pages.is_empty());
+    }
+
+    #[test]
+    fn test_concat_disambiguates_locations() {
+        let loc = Location { hash: 1, disambiguator: 0, variant: 0 };
+        let mut heading = HeadingElem::new(Content::empty()).pack();
+        heading.set_location(loc);
+        let link = Meta::Link(Destination::Location(loc), Span::detached());
+        let mut frame = Frame::soft(Size::zero());
+        frame.push(Point::zero(), FrameItem::Meta(Meta::Elem(heading), Size::zero()));
+        frame.push(Point::zero(), FrameItem::Meta(link, Size::zero()));
+        let document = Document {
+            pages: vec![Page { frame, numbering: None, number: 1 }],
+            ..Default::default()
+        };
+
+        // Both documents use the same location for their heading, but the
+        // merged one has two distinct headings.
+        let merged = Document::concat([document.clone(), document]);
+        let headings = merged.introspector.query(&HeadingElem::elem().select());
+        let locs: Vec<_> = headings.iter().map(|h| h.location().unwrap()).collect();
+        assert_eq!(locs.len(), 2);
+        assert_eq!(locs[0], loc);
+        assert_ne!(locs[1], loc);
+
+        // The link on the second page points to the heading on that page.
+        let target = merged.pages[1].frame.items().find_map(|(_, item)| match item {
+            FrameItem::Meta(Meta::Link(Destination::Location(loc), _), _) => Some(*loc),
+            _ => None,
+        });
+        assert_eq!(target, Some(locs[1]));
+        assert_eq!(merged.introspector.page(locs[1]).get(), 2);
+    }
+
+    #[test]
+    fn test_concatenated_documents_share_fonts() {
+        let font = typst_assets::fonts()
+            .flat_map(|data| Font::iter(Bytes::from_static(data)))
+            .next()
+            .unwrap();
+        let id = font.ttf().glyph_index('A').unwrap().0;
+        let text = TextItem {
+            font: font.clone(),
+            size: Abs::pt(10.0),
+            fill: Paint::Solid(Color::BLACK),
+            stroke: None,
+            lang: Lang::ENGLISH,
+            text: "A".into(),
+            glyphs: vec![Glyph {
+                id,
+                x_advance: font.advance(id).unwrap(),
+                x_offset: Em::zero(),
+                range: 0..1,
+                span: (Span::detached(), 0),
+            }],
+        };
+        let mut frame = Frame::soft(Size::splat(Abs::pt(100.0)));
+        frame.push(Point::with_y(Abs::pt(20.0)), FrameItem::Text(text));
+        let document = |pages: usize| Document {
+            pages: (1..=pages)
+                .map(|number| Page { frame: frame.clone(), numbering: None, number })
+                .collect(),
+            ..Default::default()
+        };
+
+        // The pages of all documents still refer to the one font, so that
+        // exporters embed it only once.
+        let merged = Document::concat([document(1), document(2), document(1)]);
+        assert_eq!(merged.pages.len(), 4);
+        for page in &merged.pages {
+            let Some((_, FrameItem::Text(text))) = page.frame.items().next() else {
+                panic!("page without text");
+            };
+            assert_eq!(text.font, font);
+        }
+    }
 
     #[test]
     fn test_document_is_send_and_sync() {
@@ -92,7 +92,9 @@ impl LinkElem {
 impl Show for Packed<LinkElem> {
     #[typst_macros::time(name = "link", span = self.span())]
     fn show(&self, engine: &mut Engine, _: StyleChain) -> SourceResult<Content> {
-        let body = self.body().clone();
+        // Generated bodies take the link's span, which identifies the link in
+        // the exported frames.
+        let body = self.body().clone().spanned(self.span());
         let linked = match self.dest() {
             LinkTarget::Dest(dest) => body.linked(dest.clone()),
             LinkTarget::Label(label) => {
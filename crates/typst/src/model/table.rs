@@ -1,24 +1,28 @@
 use std::num::NonZeroUsize;
 use std::sync::Arc;
 
+use comemo::{Track, Tracked, TrackedMut};
 use ecow::eco_format;
 
 use crate::diag::{bail, SourceResult, Trace, Tracepoint};
-use crate::engine::Engine;
+use crate::engine::{Engine, Route};
+use crate::eval::Tracer;
 use crate::foundations::{
     cast, elem, scope, Content, Fold, Packed, Show, Smart, StyleChain,
 };
+use crate::introspection::{Introspector, Locator};
 use crate::layout::{
-    show_grid_cell, Abs, Alignment, Axes, Cell, CellGrid, Celled, Dir, Fragment,
-    GridCell, GridHLine, GridItem, GridLayouter, GridVLine, LayoutMultiple, Length,
-    LinePosition, OuterHAlignment, OuterVAlignment, Regions, Rel, ResolvableCell, Sides,
-    TrackSizings,
+    show_grid_cell, Abs, Alignment, Angle, Axes, Cell, CellGrid, CellOverflow, Celled,
+    ColumnSizings, Dir, Fragment, GridCell, GridFooter, GridHLine, GridHeader, GridItem,
+    GridLayouter, GridVLine, LayoutMultiple, Length, LinePosition, OuterHAlignment,
+    OuterVAlignment, Regions, Rel, ResolvableCell, RowGroup, Sides, TrackSizings,
 };
 use crate::model::Figurable;
 use crate::syntax::Span;
 use crate::text::{Lang, LocalName, Region, TextElem};
 use crate::util::NonZeroExt;
 use crate::visualize::{Paint, Stroke};
+use crate::World;
 
 /// A table of items.
 ///
@@ -97,7 +101,7 @@ pub struct TableElem {
     /// The column sizes. See the [grid documentation]($grid) for more
     /// information on track sizing.
     #[borrowed]
-    pub columns: TrackSizings,
+    pub columns: ColumnSizings,
 
     /// The row sizes. See the [grid documentation]($grid) for more information
     /// on track sizing.
@@ -124,6 +128,94 @@ pub struct TableElem {
     #[borrowed]
     pub row_gutter: TrackSizings,
 
+    /// Whether to collapse gutters next to empty cells.
+    ///
+    /// When enabled, a gap between two columns is removed if, in every row,
+    /// the cell on at least one of its sides is empty, such that the
+    /// remaining cells sit closer together. Gaps between rows collapse in the
+    /// same way.
+    ///
+    /// ```example
+    /// #table(
+    ///   columns: 3,
+    ///   gutter: 8pt,
+    ///   collapse-gutter: true,
+    ///   [A], [], [C],
+    /// )
+    /// ```
+    #[default(false)]
+    pub collapse_gutter: bool,
+
+    /// Columns that keep their natural width when the table is too wide.
+    ///
+    /// If auto columns don't fit, they are usually all shrunk. Pinned columns
+    /// (given by their zero-based index) are left out, so only the other auto
+    /// columns give up space. The pinned columns are only shrunk as well if
+    /// they don't fit on their own.
+    ///
+    /// ```example
+    /// #table(
+    ///   columns: 2,
+    ///   pinned-columns: (0,),
+    ///   [*Description*], lorem(20),
+    /// )
+    /// ```
+    #[borrowed]
+    pub pinned_columns: Vec<usize>,
+
+    /// Auto columns that should end up with the same width.
+    ///
+    /// After the auto columns are measured and, if necessary, shrunk, the
+    /// listed columns (given by their zero-based index) are all set to the
+    /// average of their widths. This keeps the table's total width unchanged.
+    /// Any rounding remainder is left unused at the end of the table, such
+    /// that the listed columns are exactly equal and the table never becomes
+    /// wider than the available space.
+    ///
+    /// ```example
+    /// #table(
+    ///   columns: 2,
+    ///   equal-columns: (0, 1),
+    ///   [Short], lorem(4),
+    /// )
+    /// ```
+    #[borrowed]
+    pub equal_columns: Vec<usize>,
+
+    /// How to handle cell content that is too wide for its cell.
+    ///
+    /// - `{"clip"}`: The content is cut off at the cell's edges.
+    /// - `{"shrink"}`: The content is scaled down until it fits.
+    /// - `{"break"}`: The content wraps onto additional lines, each as wide as
+    ///   the cell.
+    /// - `{"overflow"}`: The content bleeds past the cell's edges (default).
+    ///
+    /// ```example
+    /// #table(
+    ///   columns: (40pt, 40pt),
+    ///   overflow: "shrink",
+    ///   rect(width: 80pt), [B],
+    /// )
+    /// ```
+    pub overflow: CellOverflow,
+
+    /// Groups of rows that should stay together.
+    ///
+    /// Each group is given as an array of its first row and the row after its
+    /// last one (both zero-based). If a group doesn't fit into the remaining
+    /// space of a page, it moves to the next page as a whole. A group that is
+    /// too large for a page on its own breaks like regular rows.
+    ///
+    /// ```example
+    /// #set page(height: 80pt)
+    /// #table(
+    ///   row-groups: ((1, 3),),
+    ///   [A], [B], [C],
+    /// )
+    /// ```
+    #[borrowed]
+    pub row_groups: Vec<RowGroup>,
+
     /// How to fill the cells.
     ///
     /// This can be a color or a function that returns a color. The function is
@@ -147,6 +239,29 @@ pub struct TableElem {
     #[borrowed]
     pub fill: Celled<Option<Paint>>,
 
+    /// How to fill whole rows.
+    ///
+    /// Unlike with `fill`, each row is filled with one continuous band that
+    /// also covers the gaps between columns, which is useful for banded
+    /// tables. This can be a color, an array of colors that is cycled through
+    /// the rows, or a function that is passed the row index, starting at zero,
+    /// and returns a color. A gap between two rows is filled if both rows
+    /// have the same band. Cell fills are drawn on top of row bands.
+    ///
+    /// ```example
+    /// #table(
+    ///   columns: 3,
+    ///   gutter: 4pt,
+    ///   inset: 4pt,
+    ///   row-fill: (none, luma(230)),
+    ///   [A], [B], [C],
+    ///   [D], [E], [F],
+    ///   [G], [H], [I],
+    /// )
+    /// ```
+    #[borrowed]
+    pub row_fill: Celled<Option<Paint>>,
+
     /// How to align the cells' content.
     ///
     /// This can either be a single alignment, an array of alignments
@@ -221,6 +336,12 @@ impl TableElem {
 
     #[elem]
     type TableVLine;
+
+    #[elem]
+    type TableHeader;
+
+    #[elem]
+    type TableFooter;
 }
 
 impl LayoutMultiple for Packed<TableElem> {
@@ -231,20 +352,58 @@ impl LayoutMultiple for Packed<TableElem> {
         styles: StyleChain,
         regions: Regions,
     ) -> SourceResult<Fragment> {
-        let inset = self.inset(styles);
-        let align = self.align(styles);
-        let columns = self.columns(styles);
-        let rows = self.rows(styles);
-        let column_gutter = self.column_gutter(styles);
-        let row_gutter = self.row_gutter(styles);
-        let fill = self.fill(styles);
-        let stroke = self.stroke(styles);
-
-        let tracks = Axes::new(columns.0.as_slice(), rows.0.as_slice());
+        let grid = resolve_table(self, engine, styles)?;
+        let layouter = GridLayouter::new(&grid, regions, styles, self.span());
+        layouter.layout(engine)
+    }
+}
+
+/// Resolves the cells, tracks and lines of the table.
+///
+/// This is memoized, so that laying out the same table into different regions,
+/// e.g. when it is measured before being placed, reuses the resolved cells.
+/// Since these keep their cached hashes, the memoized layouts of the cells
+/// don't have to hash them again.
+fn resolve_table(
+    elem: &Packed<TableElem>,
+    engine: &mut Engine,
+    styles: StyleChain,
+) -> SourceResult<Arc<CellGrid>> {
+    #[comemo::memoize]
+    fn cached(
+        elem: &Packed<TableElem>,
+        world: Tracked<dyn World + '_>,
+        introspector: Tracked<Introspector>,
+        route: Tracked<Route>,
+        locator: Tracked<Locator>,
+        tracer: TrackedMut<Tracer>,
+        styles: StyleChain,
+    ) -> SourceResult<Arc<CellGrid>> {
+        let mut locator = Locator::chained(locator);
+        let mut engine = Engine {
+            world,
+            introspector,
+            route: Route::extend(route).unnested(),
+            locator: &mut locator,
+            tracer,
+        };
+
+        let inset = elem.inset(styles);
+        let align = elem.align(styles);
+        let columns = elem.columns(styles);
+        let rows = elem.rows(styles);
+        let column_gutter = elem.column_gutter(styles);
+        let row_gutter = elem.row_gutter(styles);
+        let fill = elem.fill(styles);
+        let row_fill = elem.row_fill(styles);
+        let stroke = elem.stroke(styles);
+
+        let sizings = columns.sizings();
+        let tracks = Axes::new(sizings.as_slice(), rows.0.as_slice());
         let gutter = Axes::new(column_gutter.0.as_slice(), row_gutter.0.as_slice());
         // Use trace to link back to the table when a specific cell errors
         let tracepoint = || Tracepoint::Call(Some(eco_format!("table")));
-        let items = self.children().iter().map(|child| match child {
+        let items = elem.children().iter().map(|child| match child {
             TableChild::HLine(hline) => GridItem::HLine {
                 y: hline.y(styles),
                 start: hline.start(styles),
@@ -275,25 +434,63 @@ impl LayoutMultiple for Packed<TableElem> {
                     OuterHAlignment::End | OuterHAlignment::Right => LinePosition::After,
                 },
             },
+            TableChild::Header(header) => GridItem::Header {
+                repeat: header.repeat(styles),
+                span: header.span(),
+                cells: header
+                    .children()
+                    .iter()
+                    .map(|cell| Packed::new(cell.clone()).spanned(header.span()))
+                    .collect(),
+            },
+            TableChild::Footer(footer) => GridItem::Footer {
+                repeat: footer.repeat(styles),
+                span: footer.span(),
+                cells: footer
+                    .children()
+                    .iter()
+                    .map(|cell| Packed::new(cell.clone()).spanned(footer.span()))
+                    .collect(),
+            },
             TableChild::Cell(cell) => GridItem::Cell(cell.clone()),
         });
-        let grid = CellGrid::resolve(
+        let mut grid = CellGrid::resolve(
             tracks,
             gutter,
             items,
             fill,
+            row_fill,
             align,
             &inset,
             &stroke,
-            engine,
+            &mut engine,
             styles,
-            self.span(),
+            elem.span(),
         )
-        .trace(engine.world, tracepoint, self.span())?;
+        .trace(engine.world, tracepoint, elem.span())?;
 
-        let layouter = GridLayouter::new(&grid, regions, styles, self.span());
-        layouter.layout(engine)
+        if elem.collapse_gutter(styles) {
+            grid.collapse_gutter();
+        }
+
+        grid.set_column_mins(&columns.mins());
+        grid.pin_columns(elem.pinned_columns(styles), elem.span())?;
+        grid.equalize_columns(elem.equal_columns(styles), elem.span())?;
+        grid.set_overflow(elem.overflow(styles));
+        grid.group_rows(elem.row_groups(styles), elem.span())?;
+
+        Ok(Arc::new(grid))
     }
+
+    cached(
+        elem,
+        engine.world,
+        engine.introspector,
+        engine.route.track(),
+        engine.locator.track(),
+        TrackedMut::reborrow_mut(&mut engine.tracer),
+        styles,
+    )
 }
 
 impl LocalName for Packed<TableElem> {
@@ -340,6 +537,8 @@ impl Figurable for Packed<TableElem> {}
 pub enum TableChild {
     HLine(Packed<TableHLine>),
     VLine(Packed<TableVLine>),
+    Header(Packed<TableHeader>),
+    Footer(Packed<TableFooter>),
     Cell(Packed<TableCell>),
 }
 
@@ -348,6 +547,8 @@ cast! {
     self => match self {
         Self::HLine(hline) => hline.into_value(),
         Self::VLine(vline) => vline.into_value(),
+        Self::Header(header) => header.into_value(),
+        Self::Footer(footer) => footer.into_value(),
         Self::Cell(cell) => cell.into_value(),
     },
     v: Content => {
@@ -366,6 +567,16 @@ cast! {
                 "cannot use `grid.vline` as a table line; use `table.vline` instead"
             );
         }
+        if v.is::<GridHeader>() {
+            bail!(
+                "cannot use `grid.header` as a table header; use `table.header` instead"
+            );
+        }
+        if v.is::<GridFooter>() {
+            bail!(
+                "cannot use `grid.footer` as a table footer; use `table.footer` instead"
+            );
+        }
         v.into()
     }
 }
@@ -376,6 +587,8 @@ impl From<Content> for TableChild {
             .into_packed::<TableHLine>()
             .map(TableChild::HLine)
             .or_else(|value| value.into_packed::<TableVLine>().map(TableChild::VLine))
+            .or_else(|value| value.into_packed::<TableHeader>().map(TableChild::Header))
+            .or_else(|value| value.into_packed::<TableFooter>().map(TableChild::Footer))
             .or_else(|value| value.into_packed::<TableCell>().map(TableChild::Cell))
             .unwrap_or_else(|value| {
                 let span = value.span();
@@ -468,6 +681,36 @@ pub struct TableVLine {
     pub position: OuterHAlignment,
 }
 
+/// A repeatable table header. See the docs for [`grid.header`]($grid.header)
+/// for more information regarding how to use this element's fields.
+#[elem(name = "header", title = "Table Header")]
+pub struct TableHeader {
+    /// Whether this header should be repeated at the top of each region.
+    /// Functions identically to the `repeat` field in
+    /// [`grid.header`]($grid.header).
+    #[default(true)]
+    pub repeat: bool,
+
+    /// The cells of the header.
+    #[variadic]
+    pub children: Vec<TableCell>,
+}
+
+/// A repeatable table footer. See the docs for [`grid.footer`]($grid.footer)
+/// for more information regarding how to use this element's fields.
+#[elem(name = "footer", title = "Table Footer")]
+pub struct TableFooter {
+    /// Whether this footer should be repeated at the bottom of each region.
+    /// Functions identically to the `repeat` field in
+    /// [`grid.footer`]($grid.footer).
+    #[default(true)]
+    pub repeat: bool,
+
+    /// The cells of the footer.
+    #[variadic]
+    pub children: Vec<TableCell>,
+}
+
 /// A cell in the table. Use this to either override table properties for a
 /// particular cell, or in show rules to apply certain styles to multiple cells
 /// at once.
@@ -522,7 +765,7 @@ pub struct TableCell {
 
     /// The cell's column (zero-indexed).
     /// Functions identically to the `x` field in [`grid.cell`]($grid.cell).
-    pub x: Smart<usize>,
+    pub x: Smart<isize>,
 
     /// The cell's row (zero-indexed).
     /// Functions identically to the `y` field in [`grid.cell`]($grid.cell).
@@ -545,6 +788,10 @@ pub struct TableCell {
     #[resolve]
     #[fold]
     pub stroke: Sides<Option<Option<Arc<Stroke>>>>,
+    /// How much to rotate the cell's content.
+    /// Functions identically to the `rotate` field in
+    /// [`grid.cell`]($grid.cell).
+    pub rotate: Angle,
 }
 
 cast! {
@@ -587,7 +834,7 @@ impl ResolvableCell for Packed<TableCell> {
         // cell stroke is the same as specifying 'none', so we equate the two
         // concepts.
         let stroke = cell_stroke.fold(stroke).map(Option::flatten);
-        cell.push_x(Smart::Custom(x));
+        cell.push_x(Smart::Custom(x as isize));
         cell.push_y(Smart::Custom(y));
         cell.push_fill(Smart::Custom(fill.clone()));
         cell.push_align(match align {
@@ -616,6 +863,7 @@ impl ResolvableCell for Packed<TableCell> {
             }),
         );
         Cell {
+            empty: self.body().is_empty(),
             body: self.pack(),
             fill,
             colspan,
@@ -624,7 +872,7 @@ impl ResolvableCell for Packed<TableCell> {
         }
     }
 
-    fn x(&self, styles: StyleChain) -> Smart<usize> {
+    fn x(&self, styles: StyleChain) -> Smart<isize> {
         (**self).x(styles)
     }
 
@@ -643,7 +891,12 @@ impl ResolvableCell for Packed<TableCell> {
 
 impl Show for Packed<TableCell> {
     fn show(&self, _engine: &mut Engine, styles: StyleChain) -> SourceResult<Content> {
-        show_grid_cell(self.body().clone(), self.inset(styles), self.align(styles))
+        show_grid_cell(
+            self.body().clone(),
+            self.inset(styles),
+            self.align(styles),
+            self.rotate(styles),
+        )
     }
 }
 
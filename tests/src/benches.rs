@@ -2,14 +2,20 @@ use comemo::{Prehashed, Track, Tracked};
 use iai::{black_box, main, Iai};
 use typst::diag::FileResult;
 use typst::eval::Tracer;
-use typst::foundations::{Bytes, Datetime};
+use typst::foundations::{Bytes, Datetime, NativeElement, Packed};
+use typst::layout::{GridCell, GridChild, GridElem};
 use typst::syntax::{FileId, Source};
-use typst::text::{Font, FontBook};
+use typst::text::{Font, FontBook, TextElem};
 use typst::visualize::Color;
 use typst::{Library, World};
 use unscanny::Scanner;
 
 const TEXT: &str = include_str!("../typ/compiler/bench.typ");
+const GRID: &str = "\
+#let cells = grid(columns: 10, ..range(2000).map(i => [Cell #i]))
+#context measure(cells).height
+#cells
+";
 
 main!(
     bench_decode,
@@ -19,6 +25,8 @@ main!(
     bench_eval,
     bench_compile,
     bench_render,
+    bench_hash_grid,
+    bench_layout_grid,
 );
 
 fn bench_decode(iai: &mut Iai) {
@@ -77,6 +85,28 @@ fn bench_render(iai: &mut Iai) {
     iai.run(|| typst_render::render(&document.pages[0].frame, 1.0, Color::WHITE))
 }
 
+fn bench_hash_grid(iai: &mut Iai) {
+    // Layout memoization hashes the grid element including all of its cells.
+    // Content caches its hash, so the cells are created anew to measure the
+    // cost of hashing them for the first time.
+    iai.run(|| {
+        let children = (0..2000).map(|i| {
+            let body = TextElem::packed(format!("Cell #{i}"));
+            GridChild::Cell(Packed::new(GridCell::new(body)))
+        });
+        typst::util::hash128(&GridElem::new(children.collect()).pack())
+    });
+}
+
+fn bench_layout_grid(iai: &mut Iai) {
+    // The grid is measured before it is placed, so it is laid out into
+    // different regions twice. Both layouts share the resolved cells instead
+    // of resolving and hashing them again.
+    let world = BenchWorld::with_text(GRID);
+    let mut tracer = Tracer::new();
+    iai.run(|| typst::compile(&world, &mut tracer));
+}
+
 struct BenchWorld {
     library: Prehashed<Library>,
     book: Prehashed<FontBook>,
@@ -86,6 +116,10 @@ struct BenchWorld {
 
 impl BenchWorld {
     fn new() -> Self {
+        Self::with_text(TEXT)
+    }
+
+    fn with_text(text: &str) -> Self {
         let data = typst_assets::fonts().next().unwrap();
         let font = Font::new(Bytes::from_static(data), 0).unwrap();
         let book = FontBook::from_fonts([&font]);
@@ -93,7 +127,7 @@ impl BenchWorld {
             library: Prehashed::new(Library::default()),
             book: Prehashed::new(book),
             font,
-            source: Source::detached(TEXT),
+            source: Source::detached(text),
         }
     }
 
@@ -10,3 +10,50 @@
   [Hello there, my friends! Hi!],
   [Hello there, my friends! Hi! What is going on right now?],
 )
+
+---
+// Test that shrunk auto columns with equal content get identical widths.
+// Ref: false
+#set page(width: 200pt, margin: 0pt)
+#let cell = layout(size => [#metadata(size.width) <width> #lorem(30)])
+#grid(columns: 2, cell, cell)
+
+#context test(query(<width>).map(m => m.value), (100pt, 100pt))
+
+---
+// Test that auto columns stay finite when a fixed column takes (almost) all
+// of the available space.
+// Ref: false
+#set page(width: 50pt, margin: 0pt)
+#let cell = layout(size => [#metadata(size.width) <width> #lorem(5)])
+#grid(columns: (50pt, auto, auto), [a], cell, cell)
+#grid(columns: (40pt, auto, auto), [a], cell, cell)
+
+#context {
+  let widths = query(<width>).map(m => m.value)
+  test(widths, (0pt, 0pt, 5pt, 5pt))
+}
+
+---
+// Test that equalized auto columns get identical widths without changing the
+// total width, both when they are shrunk and when they fit.
+// Ref: false
+#set page(width: 200pt, margin: 0pt)
+#let cell(body) = layout(size => [#metadata(size.width) <equal> #body])
+#grid(columns: 2, equal-columns: (0, 1), cell(lorem(10)), cell(lorem(40)))
+#grid(
+  columns: 3,
+  equal-columns: (0, 2),
+  cell(box(width: 20pt)),
+  box(width: 10pt),
+  cell(box(width: 40pt)),
+)
+
+#context {
+  let widths = query(<equal>).map(m => m.value)
+  test(widths, (100pt, 100pt, 30pt, 30pt))
+}
+
+---
+// Error: 2-41 cannot equalize column 2 as the grid only has 2 columns
+#grid(columns: 2, equal-columns: (0, 2))
@@ -0,0 +1,27 @@
+// Test rotated grid and table cells.
+// Ref: false
+
+---
+// A header rotated by 90 degrees is as tall as its text is wide.
+#let round(length) = calc.round(length.pt(), digits: 3)
+#context {
+  let header = cell => grid.cell(rotate: 90deg, cell)
+  let row = measure(grid(columns: 3, header[A], header[Longest], header[BB]))
+  test(round(row.height), round(measure[Longest].width))
+  test(round(row.width), round(3 * measure[Longest].height))
+}
+
+---
+// The rotated cell keeps its inset around the rotated content.
+#let round(length) = calc.round(length.pt(), digits: 3)
+#context test(
+  round(measure(table(inset: 5pt, table.cell(rotate: -90deg)[Hello])).height),
+  round(measure[Hello].width + 10pt),
+)
+
+---
+// A rotation by zero degrees leaves the cell alone.
+#context test(
+  measure(grid(grid.cell(rotate: 0deg)[Hello])),
+  measure(grid[Hello]),
+)
@@ -0,0 +1,54 @@
+// Test collapsing gutters next to empty cells.
+// Ref: false
+
+---
+// The gutters around an empty middle cell collapse, so its neighbors touch.
+#let cell = box(width: 10pt, height: 5pt)
+#context test(
+  measure(grid(columns: 3, column-gutter: 8pt, collapse-gutter: true, cell, [], cell)).width,
+  20pt,
+)
+#context test(
+  measure(grid(columns: 3, column-gutter: 8pt, cell, [], cell)).width,
+  36pt,
+)
+
+---
+// A gutter only collapses if it is next to an empty cell in every row.
+#let cell = box(width: 10pt, height: 5pt)
+#context test(
+  measure(grid(
+    columns: 3,
+    column-gutter: 8pt,
+    collapse-gutter: true,
+    cell, [], cell,
+    cell, cell, cell,
+  )).width,
+  46pt,
+)
+
+---
+// Gutters between two empty cells and between rows collapse as well.
+#let cell = box(width: 10pt, height: 5pt)
+#context test(
+  measure(grid(
+    columns: 4,
+    gutter: 8pt,
+    collapse-gutter: true,
+    cell, [], none, cell,
+    [], [], [], [],
+    cell, [], [], cell,
+  )).height,
+  10pt,
+)
+#context test(
+  measure(table(
+    columns: 3,
+    gutter: 8pt,
+    inset: 0pt,
+    stroke: none,
+    collapse-gutter: true,
+    cell, [], cell,
+  )).width,
+  20pt,
+)
@@ -0,0 +1,51 @@
+// Test grid footers.
+// Ref: false
+
+---
+// Test that a repeating footer is placed at the bottom of each region.
+#set page(height: 100pt, margin: 10pt)
+#grid(
+  columns: 2,
+  ..range(20).map(i => box(height: 10pt)[#i]),
+  grid.footer(box(height: 10pt)[#metadata(none) <footer>], []),
+)
+
+#context {
+  let footers = query(<footer>)
+  test(footers.len(), 2)
+
+  // The first region is full, so its footer ends up at the bottom of the
+  // page. In the last region, the footer directly follows the last row.
+  let (first, last) = footers.map(footer => footer.location().position())
+  test(first.page, 1)
+  test(first.y >= 80pt and first.y < 90pt, true)
+  test(last.page, 2)
+  test(last.y >= 40pt and last.y < 50pt, true)
+}
+
+---
+// Test that a non-repeating footer is only placed once.
+#set page(height: 100pt, margin: 10pt)
+#grid(
+  columns: 2,
+  ..range(20).map(i => box(height: 10pt)[#i]),
+  grid.footer(repeat: false, [#metadata(none) <footer>], []),
+)
+
+#context test(query(<footer>).len(), 1)
+
+---
+// Error: 7-38 cell in a footer cannot have an explicit position
+// Hint: 7-38 remove the cell's `x` and `y` fields
+#grid(grid.footer(grid.cell(x: 0)[a]))
+
+---
+// Error: 3:3-3:17 cannot have more than one footer
+#grid(
+  grid.footer[a],
+  grid.footer[b],
+)
+
+---
+// Error: 8-22 cannot use `grid.footer` as a table footer; use `table.footer` instead
+#table(grid.footer[a])
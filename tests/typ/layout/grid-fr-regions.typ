@@ -0,0 +1,91 @@
+// Test fractional rows in regions of different sizes.
+// Ref: false
+
+---
+// The first region only has 60pt left, so its fractional row gets what
+// remains of those instead of what would remain of the full page.
+#set page(height: 100pt, margin: 0pt)
+#set block(spacing: 0pt)
+#block(height: 40pt)
+#grid(
+  rows: (1fr, 50pt, 80pt, 1fr),
+  layout(size => [#metadata(size.height) <fr>]),
+  [#metadata(none) <second>],
+  [],
+  layout(size => [#metadata(size.height) <fr>]),
+)
+
+#context {
+  test(query(<fr>).map(m => m.value), (10pt, 20pt))
+  test(locate(<second>).position().page, 1)
+  test(locate(<second>).position().y, 50pt)
+}
+
+---
+// A grid that starts in the middle of the page shares only the space below
+// it among its fractional rows.
+#set page(height: 100pt, margin: 0pt)
+#set block(spacing: 0pt)
+#block(height: 40pt)
+#grid(
+  rows: (1fr, 20pt, 1fr),
+  layout(size => [#metadata(size.height) <fr>]),
+  [#metadata(none) <middle>],
+  layout(size => [#metadata(size.height) <fr>]),
+)
+
+#context {
+  test(query(<fr>).map(m => m.value), (20pt, 20pt))
+  test(locate(<middle>).position().page, 1)
+  test(locate(<middle>).position().y, 60pt)
+}
+
+---
+// A breakable block with a fixed height gives its content a backlog of
+// differently sized regions (here 60pt and 90pt), while the full height of
+// each region stays that of the block. Each fractional row only gets what is
+// left in its own region.
+#set page(height: 100pt, margin: 0pt)
+#set block(spacing: 0pt)
+#block(height: 40pt)
+#block(height: 150pt, breakable: true, grid(
+  rows: (1fr, 40pt, 60pt, 1fr),
+  layout(size => [#metadata(size.height) <fr>]),
+  [],
+  [#metadata(none) <third>],
+  layout(size => [#metadata(size.height) <fr>]),
+))
+
+#context {
+  test(query(<fr>).map(m => m.value), (20pt, 30pt))
+  test(locate(<third>).position().page, 2)
+  test(locate(<third>).position().y, 0pt)
+}
+
+---
+// Fractional rows don't shrink below their content, and the other fractional
+// rows share what is left.
+#set page(height: 100pt, margin: 0pt)
+#grid(
+  rows: (1fr, 1fr, 40pt),
+  block(height: 50pt),
+  layout(size => [#metadata(size.height) <fr>]),
+  [#metadata(none) <last>],
+)
+
+#context {
+  test(query(<fr>).map(m => m.value), (10pt,))
+  test(locate(<fr>).position().y, 50pt)
+  test(locate(<last>).position().y, 60pt)
+}
+
+---
+// In regions of infinite height, fractional rows take their content's height.
+#set page(height: auto, margin: 0pt)
+#grid(
+  rows: (1fr, auto),
+  block(height: 30pt),
+  [#metadata(none) <after>],
+)
+
+#context test(locate(<after>).position().y, 30pt)
@@ -0,0 +1,26 @@
+// Test grids without auto columns.
+// Ref: false
+
+---
+// A purely fractional grid distributes all available space directly and
+// cells are only laid out once, with their final width.
+#let measured(id) = layout(size => [#metadata(size.width)#label(id)])
+#block(width: 90pt, grid(
+  columns: (1fr, 2fr),
+  measured("a"), measured("b"),
+))
+
+#context {
+  test(query(<a>).map(m => m.value), (30pt,))
+  test(query(<b>).map(m => m.value), (60pt,))
+}
+
+---
+// Fractional columns share what's left after relative columns.
+#let measured(id) = layout(size => [#metadata(size.width)#label(id)])
+#block(width: 100pt, grid(
+  columns: (40pt, 1fr, 50%),
+  [], measured("c"), [],
+))
+
+#context test(query(<c>).map(m => m.value), (10pt,))
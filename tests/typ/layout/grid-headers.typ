@@ -0,0 +1,85 @@
+// Test grid headers.
+// Ref: false
+
+---
+// Test that a repeating header is placed at the top of each region.
+#set page(height: 100pt, margin: 10pt)
+#grid(
+  columns: 2,
+  grid.header(box(height: 10pt)[#metadata(none) <header>], []),
+  ..range(20).map(i => box(height: 10pt)[#i]),
+)
+
+#context {
+  let headers = query(<header>)
+  test(headers.len(), 2)
+  for (i, header) in headers.enumerate() {
+    let pos = header.location().position()
+    test(pos.page, i + 1)
+    test(pos.y, 10pt)
+  }
+}
+
+---
+// Test that a repeated header takes space away from the body rows.
+#set page(height: 100pt, margin: 10pt)
+#table(
+  columns: 1,
+  inset: 0pt,
+  stroke: none,
+  table.header(box(height: 20pt)[Header]),
+  ..range(7).map(i => box(height: 20pt)[#metadata(i) <row>]),
+)
+
+#context {
+  let pages = query(<row>).map(row => row.location().page())
+  test(pages, (1, 1, 1, 2, 2, 2, 3))
+}
+
+---
+// Test that a non-repeating header is only placed once.
+#set page(height: 100pt, margin: 10pt)
+#grid(
+  columns: 2,
+  grid.header(repeat: false, [#metadata(none) <header>], []),
+  ..range(20).map(i => box(height: 10pt)[#i]),
+)
+
+#context test(query(<header>).len(), 1)
+
+---
+// Test that a header which doesn't fit into a region isn't repeated there.
+#set page(height: 100pt, margin: 10pt)
+#grid(
+  grid.header(box(height: 90pt)[#metadata(none) <header>]),
+  ..range(10).map(i => box(height: 10pt)[#metadata(i) <row>]),
+)
+
+#context {
+  test(query(<header>).len(), 1)
+  test(query(<row>).len(), 10)
+}
+
+---
+// Error: 7-38 cell in a header cannot have an explicit position
+// Hint: 7-38 remove the cell's `x` and `y` fields
+#grid(grid.header(grid.cell(y: 0)[a]))
+
+---
+// Error: 3:3-3:17 header must start at the first row
+// Hint: 3:3-3:17 try moving the header before all other cells
+#grid(
+  [a],
+  grid.header[b],
+)
+
+---
+// Error: 3:3-3:17 cannot have more than one header
+#grid(
+  grid.header[a],
+  grid.header[b],
+)
+
+---
+// Error: 8-22 cannot use `grid.header` as a table header; use `table.header` instead
+#table(grid.header[a])
@@ -0,0 +1,21 @@
+// Test that grids without fractional rows hug their content.
+// Ref: false
+
+---
+#set page(height: 200pt, margin: 0pt)
+#stack(
+  grid(rows: (10pt, 20pt, 15pt), [A], [B], [C]),
+  [#metadata(none) <after>],
+)
+
+#context test(locate(<after>).position().y, 45pt)
+
+---
+// The same grid with a fractional row expands to the full region.
+#set page(height: 200pt, margin: 0pt)
+#stack(
+  grid(rows: (10pt, 1fr, 15pt), [A], [B], [C]),
+  [#metadata(none) <after>],
+)
+
+#context test(locate(<after>).position().y, 200pt)
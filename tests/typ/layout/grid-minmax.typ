@@ -0,0 +1,56 @@
+// Test columns with a lower bound.
+// Ref: false
+
+---
+// A fractional column keeps its lower bound in a narrow region, but expands
+// in a wide one.
+#let probe(key) = layout(size => [#metadata(size.width) #label(key)])
+#let column = (min: 80pt, max: 1fr)
+#block(width: 50pt, grid(columns: (column,), probe("narrow")))
+#block(width: 200pt, grid(columns: (column, 20pt), probe("wide"), []))
+#block(width: 200pt, grid(columns: (column, 3fr), probe("shared"), []))
+#context {
+  test(query(<narrow>).first().value, 80pt)
+  test(query(<wide>).first().value, 180pt)
+  test(query(<shared>).first().value, 80pt)
+}
+
+---
+// An auto column fits its content, but is at least as wide as its lower bound.
+#context {
+  let width(body) = measure(grid(columns: ((min: 30pt),), body)).width
+  test(width(box(width: 10pt)), 30pt)
+  test(width(box(width: 50pt)), 50pt)
+}
+
+---
+// A relative upper bound is raised to the lower bound.
+#context test(measure(grid(columns: ((min: 30pt, max: 10pt),), [])).width, 30pt)
+
+---
+// A single column can be given as a dictionary, too.
+#context test(measure(grid(columns: (min: 30pt), box(width: 10pt))).width, 30pt)
+
+---
+// When auto columns must shrink, a column with a lower bound keeps it and the
+// other columns share the rest.
+#let probe(key) = layout(size => [#metadata(size.width) #label(key)])
+#let wide(key) = stack(box(width: 80pt), probe(key))
+#block(width: 100pt, grid(columns: ((min: 70pt), auto), wide("first"), wide("second")))
+#context {
+  test(query(<first>).first().value, 70pt)
+  test(query(<second>).first().value, 30pt)
+}
+
+---
+// Even if fixed columns take up all space, an auto column keeps its lower
+// bound.
+#let probe(key) = layout(size => [#metadata(size.width) #label(key)])
+#block(width: 50pt, grid(columns: (60pt, (min: 20pt)), [], probe("auto")))
+#context test(query(<auto>).first().value, 20pt)
+
+---
+// Only columns have lower bounds.
+// Error: 13-27 expected auto, relative length, or fraction, found dictionary
+#grid(rows: ((min: 10pt),), [A])
+
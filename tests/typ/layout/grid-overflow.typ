@@ -0,0 +1,39 @@
+// Test the overflow policy for over-wide cells.
+// Ref: false
+
+---
+// By default, over-wide content bleeds past its cell and keeps its size.
+#let wide = box(width: 80pt, height: 10pt)
+#context test(measure(grid(columns: (40pt,), wide)).height, 10pt)
+#context test(measure(grid(columns: (40pt,), overflow: "overflow", wide)).height, 10pt)
+
+---
+// Clipped content keeps its size, but is cut off at the cell's edges.
+#let wide = box(width: 80pt, height: 10pt)
+#context test(measure(grid(columns: (40pt,), overflow: "clip", wide)).height, 10pt)
+
+---
+// Shrunk content is scaled down to the column's width, which also makes the
+// row shorter.
+#let wide = box(width: 80pt, height: 10pt)
+#context test(measure(grid(columns: (40pt,), overflow: "shrink", wide)).height, 5pt)
+#context test(
+  measure(table(columns: (40pt,), inset: 0pt, overflow: "shrink", wide)).height,
+  5pt,
+)
+
+---
+// Broken content wraps onto as many lines as it needs, which makes the row
+// taller.
+#let wide = box(width: 80pt, height: 10pt)
+#context test(measure(grid(columns: (40pt,), overflow: "break", wide)).height, 20pt)
+#context test(measure(grid(columns: (30pt,), overflow: "break", wide)).height, 30pt)
+
+---
+// Content that fits is left alone.
+#let narrow = box(width: 20pt, height: 10pt)
+#context test(measure(grid(columns: (40pt,), overflow: "shrink", narrow)).height, 10pt)
+
+---
+// Error: 17-23 expected "clip", "shrink", "break", or "overflow"
+#grid(overflow: "wrap")
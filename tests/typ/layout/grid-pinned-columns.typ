@@ -0,0 +1,54 @@
+// Test pinning columns so that they don't shrink.
+// Ref: false
+
+---
+// A pinned column keeps its width while the other auto column shrinks.
+#let measured(id, width) = layout(size => [
+  #box(width: width, height: 5pt)#metadata(size.width)#label(id)
+])
+#block(width: 100pt, grid(
+  columns: 2,
+  pinned-columns: (0,),
+  measured("a", 70pt), measured("b", 70pt),
+))
+
+#context {
+  test(query(<a>).map(m => m.value), (70pt,))
+  test(query(<b>).map(m => m.value), (30pt,))
+}
+
+---
+// Without pinning, both columns get a fair share.
+#let measured(id, width) = layout(size => [
+  #box(width: width, height: 5pt)#metadata(size.width)#label(id)
+])
+#block(width: 100pt, grid(
+  columns: 2,
+  measured("a", 70pt), measured("b", 70pt),
+))
+
+#context {
+  test(query(<a>).map(m => m.value), (50pt,))
+  test(query(<b>).map(m => m.value), (50pt,))
+}
+
+---
+// If the pinned columns alone overflow, they shrink proportionally.
+#let measured(id, width) = layout(size => [
+  #box(width: width, height: 5pt)#metadata(size.width)#label(id)
+])
+#block(width: 100pt, grid(
+  columns: 3,
+  pinned-columns: (0, 2),
+  measured("a", 100pt), measured("b", 20pt), measured("c", 150pt),
+))
+
+#context {
+  test(query(<a>).map(m => m.value), (40pt,))
+  test(query(<b>).map(m => m.value), (0pt,))
+  test(query(<c>).map(m => m.value), (60pt,))
+}
+
+---
+// Error: 2-40 cannot pin column 2 as the grid only has 2 columns
+#grid(columns: 2, pinned-columns: (2,))
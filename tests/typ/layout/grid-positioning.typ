@@ -221,3 +221,22 @@
   fill: (x, y) => if calc.odd(x + y) { red.lighten(50%) } else { green },
   table.cell(x: 2, y: 6148914691236517206)[a],
 )
+
+---
+// Test cells placed relative to the last column.
+// Ref: false
+#show grid.cell: it => {
+  if it.body == [Total] [#metadata(it.x) <total>]
+  it
+}
+#grid(columns: 3, grid.cell(x: -1)[Total], [A], [B])
+#grid(columns: 5, grid.cell(x: -1)[Total], [A], [B])
+#grid(columns: 5, grid.cell(x: -2, colspan: 2)[Total], [A], [B])
+#context test(query(<total>).map(m => m.value), (2, 4, 3))
+
+---
+// Error: 3:3-3:22 cell could not be placed at invalid column -4
+#grid(
+  columns: 3,
+  grid.cell(x: -4)[A],
+)
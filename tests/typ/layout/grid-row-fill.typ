@@ -0,0 +1,18 @@
+// Test row bands that span the gutter.
+// Ref: false
+
+---
+// Functions only receive the row index.
+#table(
+  columns: 2,
+  gutter: 3pt,
+  row-fill: y => {
+    test(type(y), int)
+    if calc.odd(y) { luma(230) }
+  },
+  [A], [B], [C], [D], [E], [F],
+)
+
+---
+// Error: 17-22 expected color, gradient, pattern, none, array, or function, found string
+#grid(row-fill: "hey")[A]
@@ -0,0 +1,60 @@
+// Test keeping groups of grid rows together.
+// Ref: false
+
+---
+// The group doesn't fit below the first row, so it moves to the next page as
+// a whole.
+#set page(height: 100pt, margin: 0pt)
+#grid(
+  rows: (60pt, 20pt, 20pt, 20pt),
+  row-groups: ((1, 4),),
+  [],
+  [#metadata(none) <row>],
+  [#metadata(none) <row>],
+  [#metadata(none) <row>],
+)
+
+#context test(query(<row>).map(m => m.location().page()), (2, 2, 2))
+
+---
+// Without the group, the rows break as usual.
+#set page(height: 100pt, margin: 0pt)
+#grid(
+  rows: (60pt, 20pt, 20pt, 20pt),
+  [],
+  [#metadata(none) <row>],
+  [#metadata(none) <row>],
+  [#metadata(none) <row>],
+)
+
+#context test(query(<row>).map(m => m.location().page()), (1, 1, 2))
+
+---
+// Gutter between the grouped rows counts towards the group.
+#set page(height: 100pt, margin: 0pt)
+#table(
+  rows: 20pt,
+  inset: 0pt,
+  row-gutter: 10pt,
+  row-groups: ((2, 4),),
+  [], [],
+  [#metadata(none) <row>],
+  [#metadata(none) <row>],
+)
+
+#context test(query(<row>).map(m => m.location().page()), (2, 2))
+
+---
+// A group that doesn't even fit into an empty page breaks like regular rows.
+#set page(height: 100pt, margin: 0pt)
+// Warning: 2-59 row group does not fit into a single region
+// Hint: 2-59 its rows will be broken across regions
+#grid(rows: (60pt, 60pt), row-groups: ((0, 2),), [A], [B])
+
+---
+// Error: 2-39 row group must contain at least one row
+#grid(row-groups: ((1, 1),), [A], [B])
+
+---
+// Error: 2-40 row group ends at row 3, but the grid only has 2 rows
+#table(row-groups: ((0, 3),), [A], [B])
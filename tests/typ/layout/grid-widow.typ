@@ -0,0 +1,16 @@
+// Test that cells breaking across regions don't strand single lines.
+// Ref: false
+
+---
+// Only three of the four lines fit on the first page, but the last line is
+// not left alone on the second page.
+#set page(height: 40pt, margin: 0pt)
+#set text(10pt)
+#grid(
+  [A #metadata(none) <line> \
+   B #metadata(none) <line> \
+   C #metadata(none) <line> \
+   D #metadata(none) <line>],
+)
+
+#context test(query(<line>).map(m => m.location().page()), (1, 1, 2, 2))